@@ -1,17 +1,55 @@
 use image::{Rgb, RgbImage};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use rayon::{prelude::*, ThreadPoolBuilder};
 
 use crate::{
-    objects::{camera::Camera, object3d::Object3D, ray::Ray},
+    objects::{camera::Camera, light::Light, material::Material, object3d::Object3D, ray::Ray},
+    settings::{Integrator, RenderSettings},
     tools::{
-        color_tools::ColorType,
+        bvh::Bvh,
+        color_tools::{Color, ColorType},
+        film::Film,
         intersectable::{Intersectable, Intersection},
+        running_stats::RunningStats,
+        sampling,
         vector3::Vector3,
     },
 };
 
+static AMBIENT: f32 = 0.1;
+/// Offset applied along the normal to secondary ray origins so a surface does
+/// not intersect itself due to floating point error.
+static SURFACE_BIAS: f32 = 1e-3;
+/// Bounces after which paths start being terminated by Russian roulette.
+static ROULETTE_DEPTH: u32 = 3;
+/// Side, in pixels, of the square tiles the image is rendered in.
+static TILE_SIZE: usize = 16;
+/// Brightness under which adaptive sampling measures a pixel's error in
+/// absolute rather than relative terms, so that dark pixels converge too.
+static MIN_ERROR_BRIGHTNESS: f32 = 0.01;
+
+/// Block of the image rendered as one unit of work.
+struct Tile {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+/// Everything to render. Tiles are rendered on several threads sharing the
+/// scene, so everything it holds has to be `Sync`.
 pub struct Scene<'a> {
     camera: Camera,
     objects: Vec<Object3D<'a>>,
+    /// Hierarchy over the bounded objects, whose indices into `objects` are in
+    /// `bounded`; the rest (planes) are tested one by one.
+    bvh: Bvh,
+    bounded: Vec<usize>,
+    unbounded: Vec<usize>,
+    lights: Vec<Light>,
+    /// Emissive spheres among `objects`, sampled as area lights.
+    area_lights: Vec<Light>,
+    settings: RenderSettings,
 }
 
 impl<'a> Scene<'a> {
@@ -19,19 +57,98 @@ impl<'a> Scene<'a> {
         Self {
             camera,
             objects: Vec::new(),
+            bvh: Bvh::new(&[]),
+            bounded: Vec::new(),
+            unbounded: Vec::new(),
+            lights: Vec::new(),
+            area_lights: Vec::new(),
+            settings: RenderSettings::default(),
         }
     }
 
     pub fn set_objects(&mut self, objects: Vec<Object3D<'a>>) {
         self.objects = objects;
+        self.area_lights = self
+            .objects
+            .iter()
+            .filter_map(Scene::register_area_light)
+            .collect();
+        self.build_bvh();
     }
 
+    /// Adds one object. This rebuilds the BVH, so prefer [`Scene::set_objects`]
+    /// for many objects.
     pub fn add_object(&mut self, object: Object3D<'a>) {
+        self.area_lights.extend(Scene::register_area_light(&object));
         self.objects.push(object);
+        self.build_bvh();
+    }
+
+    fn build_bvh(&mut self) {
+        let mut bounds = Vec::new();
+        self.bounded.clear();
+        self.unbounded.clear();
+
+        for (index, object) in self.objects.iter().enumerate() {
+            match object.get_bounds() {
+                Some(object_bounds) => {
+                    bounds.push(object_bounds);
+                    self.bounded.push(index);
+                }
+                None => self.unbounded.push(index),
+            }
+        }
+
+        self.bvh = Bvh::new(&bounds);
+    }
+
+    fn area_light(object: &Object3D) -> Option<Light> {
+        match *object {
+            Object3D::Sphere(sphere) => Light::from_sphere(sphere),
+            _ => None,
+        }
+    }
+
+    /// Like [`Scene::area_light`], warning about emissive spheres that cannot
+    /// be sampled as lights.
+    fn register_area_light(object: &Object3D) -> Option<Light> {
+        let light = Scene::area_light(object);
+
+        if let Object3D::Sphere(sphere) = *object {
+            if light.is_none() && Vector3::max_component(&sphere.material.get_emission()) > 0.0 {
+                println!(
+                    "Warning: emissive sphere at {} is not uniformly scaled; it only lights paths that hit it",
+                    sphere.transform.point_to_world(&sphere.position)
+                );
+            }
+        }
+
+        return light;
+    }
+
+    /// Scene lights followed by the area lights of emissive objects.
+    fn all_lights(&self) -> impl Iterator<Item = &Light> {
+        return self.lights.iter().chain(self.area_lights.iter());
+    }
+
+    pub fn set_lights(&mut self, lights: Vec<Light>) {
+        self.lights = lights;
+    }
+
+    pub fn add_light(&mut self, light: Light) {
+        self.lights.push(light);
+    }
+
+    pub fn set_render_settings(&mut self, settings: RenderSettings) {
+        if let Some(projection) = settings.projection {
+            self.camera.projection = projection;
+        }
+
+        self.settings = settings;
     }
 
     pub fn generate_raytraced_image(&self) {
-        let pixel_buffer = self.raytrace();
+        let (pixel_buffer, sample_counts) = self.raytrace();
         let mut img = RgbImage::new(self.camera.width as u32, self.camera.height as u32);
 
         for y in 0..self.camera.height {
@@ -44,10 +161,47 @@ impl<'a> Scene<'a> {
             Ok(_) => println!("Image correctly saved to image.bmp"),
             Err(e) => println!("{}", e.to_string()),
         }
+
+        let total: u64 = sample_counts.iter().flatten().map(|&n| n as u64).sum();
+        println!(
+            "samples per pixel: {:.2}",
+            total as f32 / (self.camera.width * self.camera.height) as f32
+        );
+
+        if let Some(adaptive) = &self.settings.adaptive_sampling {
+            if let Some(path) = &adaptive.heatmap_path {
+                let heatmap = Scene::sample_heatmap(
+                    &sample_counts,
+                    adaptive.min_samples,
+                    adaptive.max_samples,
+                );
+
+                match heatmap.save(path) {
+                    Ok(_) => println!("Sample heatmap saved to {}", path),
+                    Err(e) => println!("{}", e),
+                }
+            }
+        }
     }
 
-    pub fn raytrace(&self) -> Vec<Vec<ColorType>> {
-        let pos_raytrace = self.camera.calculate_ray_positions();
+    /// Image of the number of samples taken in each pixel, from blue at `min`
+    /// to red at `max`.
+    fn sample_heatmap(sample_counts: &[Vec<u32>], min: u32, max: u32) -> RgbImage {
+        let height = sample_counts.len();
+        let width = sample_counts.first().map_or(0, |row| row.len());
+        let range = max.saturating_sub(min).max(1) as f32;
+
+        return RgbImage::from_fn(width as u32, height as u32, |x, y| {
+            let t =
+                (sample_counts[y as usize][x as usize].saturating_sub(min) as f32 / range).min(1.0);
+
+            Color::from_vector3(&Vector3::new(t, 0.0, 1.0 - t))
+        });
+    }
+
+    /// Renders the image, returned along with the number of samples taken in
+    /// each pixel.
+    pub fn raytrace(&self) -> (Vec<Vec<ColorType>>, Vec<Vec<u32>>) {
         let mut pixel_buffer: Vec<Vec<ColorType>> =
             vec![vec![Rgb([0, 0, 0]); self.camera.width]; self.camera.height];
 
@@ -58,37 +212,647 @@ impl<'a> Scene<'a> {
             pixel_buffer[0].len()
         );
 
-        for y in 0..self.camera.height {
-            for x in 0..self.camera.width {
-                let curr_x = pos_raytrace[y][x].x + self.camera.position.x;
-                let curr_y = pos_raytrace[y][x].y + self.camera.position.y;
-                let curr_z = pos_raytrace[y][x].z + self.camera.position.z;
-
-                let ray = Ray::new(&self.camera.position, &Vector3::new(curr_x, curr_y, curr_z));
-
-                match self.raycast(ray) {
-                    Some(inter) => {
-                        pixel_buffer[y][x] = {
-                            match inter.object {
-                                Object3D::Sphere(sphere) => sphere.color.rgb(),
-                                Object3D::Plane(plane) => plane.color.rgb(),
-                                Object3D::Triangle(triangle) => triangle.color.rgb(),
-                            }
-                        }
+        let tiles: Vec<Tile> = (0..self.camera.height)
+            .step_by(TILE_SIZE)
+            .flat_map(|y| {
+                (0..self.camera.width)
+                    .step_by(TILE_SIZE)
+                    .map(move |x| Tile {
+                        x,
+                        y,
+                        width: TILE_SIZE.min(self.camera.width - x),
+                        height: TILE_SIZE.min(self.camera.height - y),
+                    })
+            })
+            .collect();
+        let render_tiles = || -> Vec<(Film, Vec<u32>)> {
+            return tiles
+                .par_iter()
+                .map(|tile| self.render_tile(tile))
+                .collect();
+        };
+
+        // rayon picks one thread per core for 0.
+        let rendered = match ThreadPoolBuilder::new()
+            .num_threads(self.settings.threads.unwrap_or(0))
+            .build()
+        {
+            Ok(pool) => pool.install(render_tiles),
+            Err(e) => {
+                println!("Warning: cannot create the render threads: {}", e);
+                render_tiles()
+            }
+        };
+
+        // Tiles splat samples over their neighbours' pixels too; merging them
+        // in a fixed order keeps the sums independent of the thread count.
+        let mut film = Film::new(self.camera.width, self.camera.height, self.settings.filter);
+        let mut sample_counts = vec![vec![0; self.camera.width]; self.camera.height];
+
+        for (tile, (tile_film, tile_counts)) in tiles.iter().zip(rendered) {
+            film.merge(&tile_film);
+
+            for (i, &count) in tile_counts.iter().enumerate() {
+                sample_counts[tile.y + i / tile.width][tile.x + i % tile.width] = count;
+            }
+        }
+
+        for (y, row) in pixel_buffer.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = Color::from_vector3(&film.get_pixel(x, y));
+            }
+        }
+
+        return (pixel_buffer, sample_counts);
+    }
+
+    /// Samples the pixels of `tile` into a film covering the tile and the
+    /// pixels around it within reach of the filter. Also returns the number
+    /// of samples taken in each pixel of the tile, row by row.
+    fn render_tile(&self, tile: &Tile) -> (Film, Vec<u32>) {
+        let radius = self.settings.filter.radius().ceil() as usize;
+        let origin_x = tile.x.saturating_sub(radius);
+        let origin_y = tile.y.saturating_sub(radius);
+        let mut film = Film::new_region(
+            origin_x,
+            origin_y,
+            (tile.x + tile.width + radius).min(self.camera.width) - origin_x,
+            (tile.y + tile.height + radius).min(self.camera.height) - origin_y,
+            self.settings.filter,
+        );
+        let mut sample_counts = Vec::with_capacity(tile.width * tile.height);
+
+        let adaptive = self.settings.adaptive_sampling.as_ref();
+        let (min_samples, max_samples) = match adaptive {
+            // A single sample has no variance to judge convergence by.
+            Some(adaptive) => (
+                adaptive.min_samples.max(2),
+                adaptive.max_samples.max(adaptive.min_samples).max(2),
+            ),
+            None => {
+                let samples = self.settings.samples_per_pixel.max(1);
+                (samples, samples)
+            }
+        };
+        let strata = ((min_samples as f32).sqrt() as u32).max(1);
+
+        for y in tile.y..tile.y + tile.height {
+            for x in tile.x..tile.x + tile.width {
+                // Seeded per pixel so renders are reproducible.
+                let mut rng = SmallRng::seed_from_u64((y * self.camera.width + x) as u64);
+                let mut brightness = RunningStats::new();
+
+                for sample in 0..max_samples {
+                    // A lone sample goes through the pixel center.
+                    let (offset_x, offset_y) = if max_samples == 1 {
+                        (0.5, 0.5)
+                    } else {
+                        sampling::stratified_square(sample, strata, &mut rng)
+                    };
+                    let film_x = x as f32 + offset_x;
+                    let film_y = y as f32 + offset_y;
+
+                    let radiance = match self.camera.get_ray(film_x, film_y, rng.gen()) {
+                        Some(ray) => self.trace_camera_ray(&ray, &mut rng),
+                        None => Vector3::zero(),
+                    };
+                    film.add_sample(film_x, film_y, &radiance);
+
+                    let displayed = Vector3::new(
+                        radiance.x.clamp(0.0, 1.0),
+                        radiance.y.clamp(0.0, 1.0),
+                        radiance.z.clamp(0.0, 1.0),
+                    );
+                    brightness.add(Color::luminance(&displayed));
+
+                    if brightness.get_count() >= min_samples
+                        && adaptive.is_some_and(|adaptive| {
+                            brightness.standard_error()
+                                < adaptive.threshold
+                                    * brightness.get_mean().max(MIN_ERROR_BRIGHTNESS)
+                        })
+                    {
+                        break;
                     }
-                    None => pixel_buffer[y][x] = image::Rgb([0, 0, 0]),
                 }
+
+                sample_counts.push(brightness.get_count());
             }
         }
 
-        return pixel_buffer;
+        return (film, sample_counts);
     }
 
-    fn raycast(&self, ray: Ray) -> Option<Intersection> {
+    /// Radiance arriving at the camera along a primary `ray`, within the
+    /// camera's clipping range.
+    fn trace_camera_ray(&self, ray: &Ray, rng: &mut SmallRng) -> Vector3 {
+        let (t_min, t_max) = self.camera.get_clip_range(ray);
+
+        return match self.settings.integrator {
+            Integrator::Whitted => self.trace(ray, t_min, t_max, 0),
+            Integrator::PathTracer => self.trace_path(ray, t_min, t_max, rng),
+        };
+    }
+
+    /// Whitted-style trace: direct lighting plus recursively traced mirror
+    /// reflections and refractions, as dictated by the hit object's material.
+    /// Only hits between `t_min` and `t_max` along `ray` are considered.
+    fn trace(&self, ray: &Ray, t_min: f32, t_max: f32, depth: u32) -> Vector3 {
+        if depth > self.settings.max_depth {
+            return Vector3::zero();
+        }
+
+        return match self.raycast(ray, t_min, t_max) {
+            Some(inter) => self.shade_material(ray, &inter, inter.object.get_material(), depth),
+            None => Vector3::zero(),
+        };
+    }
+
+    /// Monte Carlo estimate of the radiance arriving along `ray`, following a
+    /// single path of BSDF-sampled bounces. At every non-specular vertex one
+    /// light is sampled explicitly, and emitters hit by BSDF sampling are
+    /// weighted against that strategy with the power heuristic. The range
+    /// `t_min`..`t_max` only restricts the first segment of the path.
+    fn trace_path(&self, ray: &Ray, t_min: f32, t_max: f32, rng: &mut SmallRng) -> Vector3 {
+        let mut radiance = Vector3::zero();
+        let mut throughput = Vector3::new(1.0, 1.0, 1.0);
+        let mut ray = ray.clone();
+        // Pdf of the BSDF sample that produced `ray`, for weighting emitters it hits.
+        let mut bsdf_pdf = 0.0;
+        let mut specular_bounce = true;
+        let mut range = (t_min, t_max);
+
+        for depth in 0..=self.settings.max_depth {
+            let inter = match self.raycast(&ray, range.0, range.1) {
+                Some(inter) => inter,
+                None => break,
+            };
+            let material = inter.object.get_material();
+            let emission = material.get_emission();
+
+            if Vector3::max_component(&emission) > 0.0 {
+                let weight = if specular_bounce {
+                    1.0
+                } else {
+                    let light_pdf = match Scene::area_light(&inter.object) {
+                        Some(light) => light.pdf(&ray.origin) * self.light_selection_pdf(),
+                        None => 0.0,
+                    };
+
+                    sampling::power_heuristic(bsdf_pdf, light_pdf)
+                };
+
+                radiance = Vector3::add(
+                    &radiance,
+                    &Vector3::scalar_multiplication(
+                        &Vector3::component_multiplication(&throughput, &emission),
+                        weight,
+                    ),
+                );
+            }
+
+            if !material.is_specular() {
+                radiance = Vector3::add(
+                    &radiance,
+                    &Vector3::component_multiplication(
+                        &throughput,
+                        &self.sample_direct_light(&ray, &inter, rng),
+                    ),
+                );
+            }
+
+            let sample = match material.sample(&ray.direction, &inter, rng) {
+                Some(sample) => sample,
+                None => break,
+            };
+            throughput = Vector3::component_multiplication(&throughput, &sample.weight);
+            bsdf_pdf = sample.pdf;
+            specular_bounce = sample.is_specular;
+
+            if depth >= ROULETTE_DEPTH {
+                let survival = Vector3::max_component(&throughput).clamp(0.05, 0.95);
+
+                if rng.gen::<f32>() > survival {
+                    break;
+                }
+
+                throughput = Vector3::scalar_multiplication(&throughput, 1.0 / survival);
+            }
+
+            ray = Scene::offset_ray(&inter, &sample.direction);
+            range = (0.0, f32::INFINITY);
+        }
+
+        return radiance;
+    }
+
+    /// Probability of picking any given light in [`Scene::sample_direct_light`].
+    fn light_selection_pdf(&self) -> f32 {
+        return 1.0 / (self.lights.len() + self.area_lights.len()).max(1) as f32;
+    }
+
+    /// Next-event estimation: radiance reflected back along `ray` at `inter`
+    /// from one uniformly chosen light, MIS-weighted against BSDF sampling for
+    /// area lights.
+    fn sample_direct_light(&self, ray: &Ray, inter: &Intersection, rng: &mut SmallRng) -> Vector3 {
+        let light_count = self.lights.len() + self.area_lights.len();
+
+        if light_count == 0 {
+            return Vector3::zero();
+        }
+
+        let light = match self.all_lights().nth(rng.gen_range(0..light_count)) {
+            Some(light) => light,
+            None => return Vector3::zero(),
+        };
+        let sample = match light.sample(&inter.point, rng) {
+            Some(sample) => sample,
+            None => return Vector3::zero(),
+        };
+
+        let material = inter.object.get_material();
+        let bsdf = material.evaluate(&ray.direction, &sample.direction, inter);
+
+        if Vector3::max_component(&bsdf) <= 0.0 || sample.pdf <= 0.0 {
+            return Vector3::zero();
+        }
+
+        let shadow_ray = Scene::offset_ray(inter, &sample.direction);
+
+        if self.is_occluded(&shadow_ray, sample.distance - SURFACE_BIAS) {
+            return Vector3::zero();
+        }
+
+        let light_pdf = sample.pdf * self.light_selection_pdf();
+        let weight = if sample.is_delta {
+            1.0
+        } else {
+            sampling::power_heuristic(
+                light_pdf,
+                material.pdf(&ray.direction, &sample.direction, inter),
+            )
+        };
+
+        return Vector3::scalar_multiplication(
+            &Vector3::component_multiplication(&bsdf, &sample.radiance),
+            weight / light_pdf,
+        );
+    }
+
+    fn shade_material(
+        &self,
+        ray: &Ray,
+        inter: &Intersection,
+        material: &Material,
+        depth: u32,
+    ) -> Vector3 {
+        match material {
+            Material::Diffuse {
+                specular,
+                shininess,
+                ..
+            } => self.shade(
+                ray,
+                inter,
+                &material.get_albedo(inter),
+                *specular,
+                *shininess,
+            ),
+            Material::Metal { albedo, roughness } => {
+                let shininess = Material::roughness_to_shininess(*roughness);
+                let highlight = self.shade(ray, inter, &Vector3::zero(), 1.0, shininess);
+                let reflected = self.trace_reflection(ray, inter, depth);
+
+                Vector3::component_multiplication(&Vector3::add(&highlight, &reflected), albedo)
+            }
+            Material::Dielectric {
+                tint,
+                refractive_index,
+            } => {
+                let reflected = self.trace_reflection(ray, inter, depth);
+                let eta = if inter.front_face {
+                    1.0 / refractive_index
+                } else {
+                    *refractive_index
+                };
+
+                // Total internal reflection sends everything down the reflected path.
+                match Vector3::refract(&ray.direction, &inter.shading_normal, eta) {
+                    Some(direction) => {
+                        let cos_i = -Vector3::dot_product(&ray.direction, &inter.shading_normal);
+                        let cos_t = -Vector3::dot_product(&direction, &inter.shading_normal);
+                        // Schlick uses the angle on the optically less dense side.
+                        let cosine = if eta > 1.0 { cos_t } else { cos_i };
+                        let fresnel = Material::schlick(cosine, *refractive_index);
+                        let refracted = Vector3::component_multiplication(
+                            &self.trace(
+                                &Scene::offset_ray(inter, &direction),
+                                0.0,
+                                f32::INFINITY,
+                                depth + 1,
+                            ),
+                            tint,
+                        );
+
+                        Vector3::add(
+                            &Vector3::scalar_multiplication(&reflected, fresnel),
+                            &Vector3::scalar_multiplication(&refracted, 1.0 - fresnel),
+                        )
+                    }
+                    None => reflected,
+                }
+            }
+            Material::Emissive { emission } => *emission,
+            Material::Mix {
+                first,
+                second,
+                factor,
+            } => Vector3::add(
+                &Vector3::scalar_multiplication(
+                    &self.shade_material(ray, inter, first, depth),
+                    1.0 - factor,
+                ),
+                &Vector3::scalar_multiplication(
+                    &self.shade_material(ray, inter, second, depth),
+                    *factor,
+                ),
+            ),
+        }
+    }
+
+    fn trace_reflection(&self, ray: &Ray, inter: &Intersection, depth: u32) -> Vector3 {
+        let direction = Vector3::reflect(&ray.direction, &inter.shading_normal);
+
+        return self.trace(
+            &Scene::offset_ray(inter, &direction),
+            0.0,
+            f32::INFINITY,
+            depth + 1,
+        );
+    }
+
+    /// Ray leaving the hit point in `direction`, nudged off the surface on the
+    /// side the direction points to.
+    fn offset_ray(inter: &Intersection, direction: &Vector3) -> Ray {
+        let side = if Vector3::dot_product(direction, &inter.normal) >= 0.0 {
+            SURFACE_BIAS
+        } else {
+            -SURFACE_BIAS
+        };
+        let origin = Vector3::add(
+            &inter.point,
+            &Vector3::scalar_multiplication(&inter.normal, side),
+        );
+
+        return Ray::new(&origin, direction);
+    }
+
+    /// Closest hit along `ray` between `t_min` and `t_max`.
+    fn raycast(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection> {
+        let bounded_hit = self
+            .bvh
+            .get_intersection(ray, t_min, t_max, |index, t_max| {
+                self.objects[self.bounded[index]].get_intersection(ray, t_min, t_max)
+            });
+
+        return self.unbounded.iter().fold(bounded_hit, |closest, &index| {
+            let t_max = closest.as_ref().map_or(t_max, |inter| inter.distance);
+
+            self.objects[index]
+                .get_intersection(ray, t_min, t_max)
+                .or(closest)
+        });
+    }
+
+    /// Any-hit query: true as soon as some object blocks `ray` before
+    /// `max_distance`.
+    fn is_occluded(&self, ray: &Ray, max_distance: f32) -> bool {
+        let blocks = |object: &Object3D| object.get_intersection(ray, 0.0, max_distance).is_some();
+
         return self
-            .objects
+            .unbounded
             .iter()
-            .filter_map(|s| s.get_intersection(&ray))
-            .min_by(|i1, i2| i1.distance.partial_cmp(&i2.distance).unwrap());
+            .any(|&index| blocks(&self.objects[index]))
+            || self.bvh.any_hit(ray, 0.0, max_distance, |index| {
+                blocks(&self.objects[self.bounded[index]])
+            });
+    }
+
+    /// Lambertian diffuse plus Blinn-Phong specular from every light, on top of
+    /// a constant ambient term.
+    fn shade(
+        &self,
+        ray: &Ray,
+        inter: &Intersection,
+        albedo: &Vector3,
+        specular_strength: f32,
+        shininess: f32,
+    ) -> Vector3 {
+        let point = inter.point;
+        let normal = inter.shading_normal;
+
+        let view = -ray.get_direction();
+        let mut color = Vector3::scalar_multiplication(albedo, AMBIENT);
+
+        for light in self.all_lights() {
+            let to_light = light.get_direction_to(&point);
+            let n_dot_l = Vector3::dot_product(&normal, &to_light);
+
+            if n_dot_l <= 0.0 {
+                continue;
+            }
+
+            let shadow_ray = Scene::offset_ray(inter, &to_light);
+
+            if self.is_occluded(&shadow_ray, light.get_distance_to(&shadow_ray.origin)) {
+                continue;
+            }
+
+            let half = Vector3::normalize(&Vector3::add(&to_light, &view));
+            let n_dot_h = Vector3::dot_product(&normal, &half).max(0.0);
+            let specular = specular_strength * n_dot_h.powf(shininess);
+
+            let diffuse = Vector3::scalar_multiplication(albedo, n_dot_l);
+            let reflected = Vector3::add(&diffuse, &Vector3::new(specular, specular, specular));
+
+            color = Vector3::add(
+                &color,
+                &Vector3::component_multiplication(&reflected, &light.get_radiance_at(&point)),
+            );
+        }
+
+        return color;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::{camera::Projection, plane::Plane, sphere::Sphere};
+    use crate::settings::AdaptiveSampling;
+    use crate::tools::filter::Filter;
+
+    /// Pinhole camera at the origin looking down +Z, with +Y up.
+    fn camera(width: usize, height: usize) -> Camera {
+        let mut camera = Camera {
+            projection: Projection::Perspective { fov_v: 60.0 },
+            width,
+            height,
+            near_plane: 0.0,
+            far_plane: f32::INFINITY,
+            aperture_radius: 0.0,
+            focus_distance: 1.0,
+            position: Vector3::zero(),
+            right: Vector3::zero(),
+            up: Vector3::zero(),
+            forward: Vector3::zero(),
+        };
+        camera.look_at(
+            Vector3::zero(),
+            Vector3::new(0.0, 0.0, 1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+        );
+
+        return camera;
+    }
+
+    fn adaptive_settings(integrator: Integrator, threshold: f32) -> RenderSettings {
+        return RenderSettings {
+            integrator,
+            samples_per_pixel: 1,
+            adaptive_sampling: Some(AdaptiveSampling {
+                min_samples: 4,
+                max_samples: 16,
+                threshold,
+                heatmap_path: None,
+            }),
+            filter: Filter::Box { radius: 0.5 },
+            projection: None,
+            max_depth: 4,
+            threads: Some(1),
+        };
+    }
+
+    #[test]
+    fn image_independent_of_thread_count() {
+        let ball = Sphere::new(
+            Vector3::new(0.0, 0.0, 5.0),
+            1.0,
+            Material::metal(Color::DarkOrange, 0.3),
+        );
+        let glass = Sphere::new(Vector3::new(1.5, -0.5, 4.0), 0.5, Material::dielectric(1.5));
+        let lamp = Sphere::new(
+            Vector3::new(-2.0, 3.0, 5.0),
+            1.0,
+            Material::emissive(Color::White, 4.0),
+        );
+        let floor = Plane::new(
+            Vector3::new(0, -1, 0),
+            Vector3::new(0, 1, 0),
+            Material::diffuse(Color::LightBlue),
+        );
+
+        // Spans several tiles, with partial ones on the right and bottom.
+        let mut camera = camera(40, 20);
+        camera.aperture_radius = 0.1;
+        camera.focus_distance = 5.0;
+
+        let mut scene = Scene::new(camera);
+        scene.set_objects(vec![
+            Object3D::Sphere(&ball),
+            Object3D::Sphere(&glass),
+            Object3D::Sphere(&lamp),
+            Object3D::Plane(&floor),
+        ]);
+        scene.set_lights(vec![Light::point(
+            Vector3::new(2.0, 2.0, 0.0),
+            Color::White,
+            10.0,
+        )]);
+
+        let settings = |threads: usize| {
+            return RenderSettings {
+                integrator: Integrator::PathTracer,
+                samples_per_pixel: 4,
+                adaptive_sampling: Some(AdaptiveSampling {
+                    min_samples: 4,
+                    max_samples: 16,
+                    threshold: 0.05,
+                    heatmap_path: None,
+                }),
+                filter: Filter::Mitchell {
+                    radius: 2.0,
+                    b: 1.0 / 3.0,
+                    c: 1.0 / 3.0,
+                },
+                projection: None,
+                max_depth: 4,
+                threads: Some(threads),
+            };
+        };
+
+        scene.set_render_settings(settings(1));
+        let (single_pixels, single_counts) = scene.raytrace();
+        scene.set_render_settings(settings(4));
+        let (pixels, counts) = scene.raytrace();
+
+        assert_eq!(single_pixels, pixels);
+        assert_eq!(single_counts, counts);
+        // Adaptive sampling actually varied the counts.
+        assert!(counts.iter().flatten().any(|&n| n != counts[0][0]));
+    }
+
+    #[test]
+    fn constant_pixels_stop_at_min_samples() {
+        let wall = Plane::new(
+            Vector3::new(0, 0, 5),
+            Vector3::new(0, 0, -1),
+            Material::emissive(Color::White, 0.5),
+        );
+
+        let mut scene = Scene::new(camera(8, 4));
+        scene.set_objects(vec![Object3D::Plane(&wall)]);
+        scene.set_render_settings(adaptive_settings(Integrator::PathTracer, 0.01));
+        let (_, counts) = scene.raytrace();
+
+        assert!(counts.iter().flatten().all(|&n| n == 4), "{:?}", counts);
+    }
+
+    #[test]
+    fn noisy_pixels_reach_max_samples() {
+        // A diffuse wall lit only by a small lamp, sampled at random points.
+        let wall = Plane::new(
+            Vector3::new(0, 0, 5),
+            Vector3::new(0, 0, -1),
+            Material::diffuse(Color::White),
+        );
+        let lamp = Sphere::new(
+            Vector3::new(0.0, 0.0, 4.0),
+            0.2,
+            Material::emissive(Color::White, 20.0),
+        );
+
+        let mut scene = Scene::new(camera(8, 4));
+        scene.set_objects(vec![Object3D::Plane(&wall), Object3D::Sphere(&lamp)]);
+        scene.set_render_settings(adaptive_settings(Integrator::PathTracer, 1e-4));
+        let (_, counts) = scene.raytrace();
+
+        assert!(counts.iter().flatten().all(|&n| n == 16), "{:?}", counts);
+    }
+
+    #[test]
+    fn heatmap_scales_with_sample_count() {
+        let heatmap = Scene::sample_heatmap(&[vec![4, 10, 16], vec![0, 20, 13]], 4, 16);
+
+        assert_eq!(heatmap.dimensions(), (3, 2));
+        assert_eq!(*heatmap.get_pixel(0, 0), Rgb([0, 0, 255]));
+        assert_eq!(*heatmap.get_pixel(1, 0), Rgb([128, 0, 128]));
+        assert_eq!(*heatmap.get_pixel(2, 0), Rgb([255, 0, 0]));
+        // Counts outside the range are clamped to it.
+        assert_eq!(*heatmap.get_pixel(0, 1), Rgb([0, 0, 255]));
+        assert_eq!(*heatmap.get_pixel(1, 1), Rgb([255, 0, 0]));
+
+        let red = |x, y| heatmap.get_pixel(x, y).0[0];
+        assert!(red(0, 0) < red(1, 0) && red(1, 0) < red(2, 1) && red(2, 1) < red(2, 0));
     }
 }
@@ -1,54 +1,127 @@
 use std::time::SystemTime;
 
+use objects::light::Light;
+use objects::material::Material;
 use objects::object3d::Object3D;
 use objects::plane::Plane;
+use settings::{Integrator, RenderSettings};
 use tools::color_tools::Color;
+use tools::filter::Filter;
 
 mod objects;
 mod scene;
+mod settings;
 mod tools;
 
-use crate::objects::{camera::Camera, sphere::Sphere};
+use crate::objects::{
+    camera::{Camera, Projection},
+    model3D::Model3D,
+    sphere::Sphere,
+};
 use crate::tools::vector3::Vector3;
 
 fn main() {
     let now = SystemTime::now();
 
-    let camera = Camera {
-        position: Vector3::new(0.0, 0.0, -4.0),
-        fov_h: 60.0,
-        fov_v: 60.0,
+    let mut camera = Camera {
+        position: Vector3::zero(),
+        projection: Projection::Perspective { fov_v: 90.0 },
         width: 400,
         height: 400,
         near_plane: 0.6,
-        fal_plane: 50.0,
-        default_z: 15.0,
+        far_plane: 50.0,
+        aperture_radius: 0.0,
+        focus_distance: 14.0,
+        // Set by `look_at` below.
+        right: Vector3::zero(),
+        up: Vector3::zero(),
+        forward: Vector3::zero(),
     };
+    camera.look_at(
+        Vector3::new(0.0, 0.0, -4.0),
+        Vector3::new(0.0, 0.0, 10.0),
+        Vector3::new(0.0, 1.0, 0.0),
+    );
+
+    let glossy = Material::metal(Color::White, 0.0);
 
-    let sphere01 = Sphere::new(Vector3::new(0.0, 0.0, 10.0), 5.0, Color::DarkRed);
-    let sphere02 = Sphere::new(Vector3::new(3.0, -2.0, 8.0), 2.0, Color::DarkGreen);
-    let sphere03 = Sphere::new(Vector3::new(-6.0, 3.0, 2.0), 1.5, Color::DarkOrange);
+    let sphere01 = Sphere::new(
+        Vector3::new(0.0, 0.0, 10.0),
+        5.0,
+        Material::mix(Material::diffuse(Color::DarkRed), glossy.clone(), 0.6),
+    );
+    let sphere02 = Sphere::new(
+        Vector3::new(-3.0, 2.0, 8.0),
+        2.0,
+        Material::mix(Material::diffuse(Color::DarkGreen), glossy.clone(), 0.3),
+    );
+    let sphere03 = Sphere::new(
+        Vector3::new(6.0, -3.0, 2.0),
+        1.5,
+        Material::metal(Color::DarkOrange, 0.3),
+    );
+    let sphere04 = Sphere::new(Vector3::new(2.0, -1.0, 1.0), 1.5, Material::dielectric(1.5));
+    let lamp = Sphere::new(
+        Vector3::new(2.0, 16.0, 2.0),
+        6.0,
+        Material::emissive(Color::White, 6.0),
+    );
     let plane01 = Plane::new(
-        Vector3::new(0, 5, -4),
+        Vector3::new(0, -5, -4),
         Vector3::new(0, 1, 0),
-        Color::DarkGray,
+        Material::diffuse(Color::DarkGray),
     );
     let plane02 = Plane::new(
         Vector3::new(0, 0, 10),
         Vector3::new(0, 0, -1),
-        Color::LightBlue,
+        Material::diffuse(Color::LightBlue),
     );
 
-    let objects: Vec<Object3D> = vec![
+    // An OBJ, PLY or STL model given on the command line joins the scene.
+    let model = std::env::args().nth(1).and_then(|path| {
+        match Model3D::new_from_file(Vector3::new(0.0, -2.0, 3.0), &path, glossy.clone()) {
+            Ok(model) => Some(model),
+            Err(e) => {
+                println!("Warning: {}", e);
+                None
+            }
+        }
+    });
+
+    let mut objects: Vec<Object3D> = vec![
         Object3D::Sphere(&sphere01),
         Object3D::Sphere(&sphere02),
         Object3D::Sphere(&sphere03),
+        Object3D::Sphere(&sphere04),
+        Object3D::Sphere(&lamp),
         Object3D::Plane(&plane01),
         Object3D::Plane(&plane02),
     ];
+    if let Some(model) = &model {
+        objects.push(Object3D::Model(model));
+    }
+
+    let lights: Vec<Light> = vec![
+        Light::directional(Vector3::new(1.0, -1.0, 1.0), Color::White, 1.0),
+        Light::point(Vector3::new(-6.0, 6.0, 0.0), Color::LightYellow, 40.0),
+    ];
 
     let mut scene: scene::Scene = scene::Scene::new(camera);
     scene.set_objects(objects);
+    scene.set_lights(lights);
+    scene.set_render_settings(RenderSettings {
+        integrator: Integrator::PathTracer,
+        samples_per_pixel: 64,
+        adaptive_sampling: None,
+        filter: Filter::Mitchell {
+            radius: 2.0,
+            b: 1.0 / 3.0,
+            c: 1.0 / 3.0,
+        },
+        projection: None,
+        max_depth: 8,
+        threads: None,
+    });
     scene.generate_raytraced_image();
 
     match now.elapsed() {
@@ -1,43 +1,400 @@
-use crate::tools::vector3::Vector3;
+use std::f32::consts::PI;
 
+use crate::tools::{matrix4::Matrix4, sampling, vector3::Vector3};
+
+use super::ray::Ray;
+
+/// Up direction used when an orientation is given by angles alone.
+static WORLD_UP: Vector3 = Vector3 {
+    x: 0.0,
+    y: 1.0,
+    z: 0.0,
+};
+
+/// How camera rays spread over the image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// Pinhole, or thin-lens with an aperture, perspective given by its
+    /// vertical field of view in degrees.
+    Perspective { fov_v: f32 },
+    /// Parallel rays along the view direction across a view `height` world
+    /// units tall, as for technical drawings.
+    Orthographic { height: f32 },
+    /// Equidistant fisheye: the angle to the view direction grows linearly
+    /// with the distance from the image center, reaching half of `fov`
+    /// degrees at the top and bottom edges.
+    Fisheye { fov: f32 },
+    /// Latitude-longitude panorama of all directions around the camera, with
+    /// the view direction at the center. Meant for 2:1 images.
+    Equirectangular,
+}
+
+/// Camera placed by its position and basis. The extent of the view follows
+/// from the projection and the aspect ratio of the image; the image's top row
+/// faces `up` and its left column faces away from `right`.
 pub struct Camera {
-    pub fov_h: f32,
-    pub fov_v: f32,
-    pub default_z: f32,
+    pub projection: Projection,
     pub width: usize,
     pub height: usize,
+    /// Clipping distances: primary rays ignore anything nearer than
+    /// `near_plane` or farther than `far_plane`, see [`Camera::get_clip_range`].
     pub near_plane: f32,
-    pub fal_plane: f32,
+    pub far_plane: f32,
+    /// Radius of the lens disk of a perspective camera; 0 keeps everything in
+    /// focus.
+    pub aperture_radius: f32,
+    /// Distance along the view direction of the plane in perfect focus.
+    pub focus_distance: f32,
     pub position: Vector3,
+    /// Right-handed orthonormal camera basis, `right` being `forward` × `up`:
+    /// the camera-space x, y and z axes in world space. The world is
+    /// right-handed too, as in glTF: looking down +Z with +Y up puts +X on
+    /// the left of the image, and looking down -Z puts it on the right.
+    pub right: Vector3,
+    pub up: Vector3,
+    pub forward: Vector3,
 }
 
 impl Camera {
-    pub fn calculate_ray_positions(&self) -> Vec<Vec<Vector3>> {
-        let mut positions: Vec<Vec<Vector3>> = vec![vec![Vector3::zero(); self.width]; self.height];
-        let angle_max_x = self.fov_h / 2.0;
-        let radius_max_x = self.default_z / angle_max_x.to_radians().cos();
+    /// Places the camera at `eye` looking at `target`. `up` only needs to be
+    /// roughly upwards; it is made orthogonal to the view direction.
+    pub fn look_at(&mut self, eye: Vector3, target: Vector3, up: Vector3) {
+        self.position = eye;
+        self.set_basis(&Vector3::sub(&target, &eye), &up);
+    }
+
+    /// Orients the camera by angles in degrees: `yaw` turns it about the world
+    /// y axis starting from +Z towards +X, `pitch` tilts it towards +Y and
+    /// `roll` then turns it about its view direction.
+    pub fn set_yaw_pitch_roll(&mut self, yaw: f32, pitch: f32, roll: f32) {
+        let (yaw_sin, yaw_cos) = yaw.to_radians().sin_cos();
+        let (pitch_sin, pitch_cos) = pitch.to_radians().sin_cos();
+        let forward = Vector3::new(yaw_sin * pitch_cos, pitch_sin, yaw_cos * pitch_cos);
+
+        // Looking straight up or down, keep the heading given by the yaw.
+        let up = if pitch_cos.abs() < 1e-6 {
+            Vector3::new(-yaw_sin * pitch_sin, 0.0, -yaw_cos * pitch_sin)
+        } else {
+            WORLD_UP
+        };
+
+        self.set_basis(&forward, &up);
+
+        let roll = Matrix4::rotation_axis_angle(&self.forward, roll);
+        self.right = Matrix4::transform_vector(&roll, &self.right);
+        self.up = Matrix4::transform_vector(&roll, &self.up);
+    }
+
+    /// Builds the orthonormal basis from a view direction and an approximate up
+    /// vector, falling back to another up vector when the two are parallel.
+    fn set_basis(&mut self, direction: &Vector3, up: &Vector3) {
+        let forward = Vector3::normalize(direction);
+        let mut right = Vector3::cross_product(&forward, up);
+
+        if Vector3::magnitude(&right) < 1e-6 {
+            let fallback = if forward.y.abs() < 0.9 {
+                WORLD_UP
+            } else {
+                Vector3::new(0.0, 0.0, 1.0)
+            };
+            right = Vector3::cross_product(&forward, &fallback);
+        }
+
+        self.right = Vector3::normalize(&right);
+        self.up = Vector3::cross_product(&self.right, &forward);
+        self.forward = forward;
+    }
+
+    /// Rotates a camera-space direction into world space.
+    pub fn to_world(&self, direction: &Vector3) -> Vector3 {
+        return Vector3::add(
+            &Vector3::add(
+                &Vector3::scalar_multiplication(&self.right, direction.x),
+                &Vector3::scalar_multiplication(&self.up, direction.y),
+            ),
+            &Vector3::scalar_multiplication(&self.forward, direction.z),
+        );
+    }
+
+    /// Primary ray through the point (`x`, `y`) of the image, in pixels from
+    /// its top-left corner, so pixel centers lie at half-integer coordinates.
+    /// `lens_sample`, a point of the unit square, picks where the ray leaves
+    /// the lens of a perspective camera with an aperture. `None` where the
+    /// projection does not reach, such as past 360° across a fisheye view.
+    pub fn get_ray(&self, x: f32, y: f32, lens_sample: (f32, f32)) -> Option<Ray> {
+        // Image coordinates in [-1, 1], y pointing up.
+        let screen_x = 2.0 * x / self.width as f32 - 1.0;
+        let screen_y = 1.0 - 2.0 * y / self.height as f32;
+        let aspect_ratio = self.aspect_ratio();
 
-        let max_x = angle_max_x.to_radians().cos() * radius_max_x;
-        let min_x = -max_x;
+        let ray = match self.projection {
+            Projection::Perspective { fov_v } => {
+                let half_height = (fov_v / 2.0).to_radians().tan();
+                let image_plane_point = Vector3::new(
+                    screen_x * half_height * aspect_ratio,
+                    screen_y * half_height,
+                    1.0,
+                );
 
-        let angle_max_y = self.fov_v / 2.0;
-        let radius_max_y = self.default_z / angle_max_y.to_radians().sin();
+                self.get_lens_ray(&image_plane_point, lens_sample)
+            }
+            Projection::Orthographic { height } => {
+                let offset = Vector3::new(
+                    screen_x * height / 2.0 * aspect_ratio,
+                    screen_y * height / 2.0,
+                    0.0,
+                );
 
-        let max_y = angle_max_y.to_radians().sin() * radius_max_y;
-        let min_y = -max_y;
+                Ray::new(
+                    &Vector3::add(&self.position, &self.to_world(&offset)),
+                    &self.forward,
+                )
+            }
+            Projection::Fisheye { fov } => {
+                let screen_x = screen_x * aspect_ratio;
+                let theta =
+                    (screen_x * screen_x + screen_y * screen_y).sqrt() * (fov / 2.0).to_radians();
 
-        let step_x = (max_x - min_x) / self.width as f32;
-        let step_y = (max_y - min_y) / self.height as f32;
+                if theta > PI {
+                    return None;
+                }
 
-        for y in 0..positions.len() {
-            for x in 0..self.width {
-                let pos_x = min_x + (step_x * x as f32);
-                let pos_y = min_y + (step_y * y as f32);
+                let phi = screen_y.atan2(screen_x);
+                let direction = Vector3::new(
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                );
 
-                positions[y][x] = Vector3::new(pos_x, pos_y, self.default_z);
+                Ray::new(&self.position, &self.to_world(&direction))
             }
+            Projection::Equirectangular => {
+                let longitude = screen_x * PI;
+                let latitude = screen_y * PI / 2.0;
+                let direction = Vector3::new(
+                    latitude.cos() * longitude.sin(),
+                    latitude.sin(),
+                    latitude.cos() * longitude.cos(),
+                );
+
+                Ray::new(&self.position, &self.to_world(&direction))
+            }
+        };
+
+        return Some(ray);
+    }
+
+    /// Perspective ray towards a point of the image plane at unit distance,
+    /// given in camera space. With an aperture the ray leaves the point of the
+    /// lens picked by `lens_sample` and passes through the focused point seen
+    /// at `image_plane_point`.
+    fn get_lens_ray(&self, image_plane_point: &Vector3, lens_sample: (f32, f32)) -> Ray {
+        if self.aperture_radius <= 0.0 {
+            return Ray::new(&self.position, &self.to_world(image_plane_point));
         }
 
-        return positions;
+        let (lens_x, lens_y) = sampling::concentric_disk(lens_sample.0, lens_sample.1);
+        let lens = Vector3::new(
+            lens_x * self.aperture_radius,
+            lens_y * self.aperture_radius,
+            0.0,
+        );
+        let focus = Vector3::scalar_multiplication(image_plane_point, self.focus_distance);
+
+        return Ray::new(
+            &Vector3::add(&self.position, &self.to_world(&lens)),
+            &self.to_world(&Vector3::sub(&focus, &lens)),
+        );
+    }
+
+    /// Range of distances along a primary `ray` between the clipping planes.
+    /// These are planes across the view direction for the perspective and
+    /// orthographic projections, and spheres around the camera for the
+    /// panoramic ones.
+    pub fn get_clip_range(&self, ray: &Ray) -> (f32, f32) {
+        if let Projection::Fisheye { .. } | Projection::Equirectangular = self.projection {
+            return (self.near_plane, self.far_plane);
+        }
+
+        let cos = Vector3::dot_product(&ray.direction, &self.forward);
+
+        if cos <= 0.0 {
+            return (f32::INFINITY, f32::INFINITY);
+        }
+
+        return (self.near_plane / cos, self.far_plane / cos);
+    }
+
+    pub fn aspect_ratio(&self) -> f32 {
+        return self.width as f32 / self.height as f32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::test_utils::assert_near;
+
+    /// 2:1 camera at (1, 2, 3) looking down -Z, with +X to the right.
+    fn camera(projection: Projection) -> Camera {
+        let mut camera = Camera {
+            projection,
+            width: 200,
+            height: 100,
+            near_plane: 0.0,
+            far_plane: f32::INFINITY,
+            aperture_radius: 0.0,
+            focus_distance: 1.0,
+            position: Vector3::zero(),
+            right: Vector3::zero(),
+            up: Vector3::zero(),
+            forward: Vector3::zero(),
+        };
+        camera.look_at(
+            Vector3::new(1.0, 2.0, 3.0),
+            Vector3::new(1.0, 2.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+        );
+
+        return camera;
+    }
+
+    fn direction(camera: &Camera, x: f32, y: f32) -> Vector3 {
+        return camera.get_ray(x, y, (0.5, 0.5)).unwrap().direction;
+    }
+
+    #[test]
+    fn perspective() {
+        let camera = camera(Projection::Perspective { fov_v: 90.0 });
+
+        assert_near(&direction(&camera, 100.0, 50.0), &Vector3::new(0, 0, -1));
+        // The top-left corner is tan(45°) up and twice that to the left.
+        assert_near(
+            &direction(&camera, 0.0, 0.0),
+            &Vector3::normalize(&Vector3::new(-2, 1, -1)),
+        );
+    }
+
+    #[test]
+    fn orthographic() {
+        let camera = camera(Projection::Orthographic { height: 4.0 });
+
+        let center = camera.get_ray(100.0, 50.0, (0.5, 0.5)).unwrap();
+        assert_near(&center.origin, &Vector3::new(1, 2, 3));
+        assert_near(&center.direction, &Vector3::new(0, 0, -1));
+
+        let corner = camera.get_ray(200.0, 100.0, (0.5, 0.5)).unwrap();
+        assert_near(&corner.origin, &Vector3::new(5, 0, 3));
+        assert_near(&corner.direction, &Vector3::new(0, 0, -1));
+    }
+
+    #[test]
+    fn fisheye() {
+        let camera = camera(Projection::Fisheye { fov: 120.0 });
+
+        assert_near(&direction(&camera, 100.0, 50.0), &Vector3::new(0, 0, -1));
+        // The top edge is 60° off the view direction.
+        assert_near(
+            &direction(&camera, 100.0, 0.0),
+            &Vector3::new(0.0, 60f32.to_radians().sin(), -60f32.to_radians().cos()),
+        );
+
+        // The top-left corner is √5 half-heights from the center, towards
+        // (-2, 1) on the image.
+        let corner = direction(&camera, 0.0, 0.0);
+        let theta = 5f32.sqrt() * 60f32.to_radians();
+        assert!((corner.z + theta.cos()).abs() < 1e-5);
+        assert!((corner.x + 2.0 * corner.y).abs() < 1e-5);
+        assert!(corner.y > 0.0);
+
+        // Wider than 360° across, the corners are out of reach.
+        let wide = Camera {
+            projection: Projection::Fisheye { fov: 180.0 },
+            ..camera
+        };
+        assert!(wide.get_ray(0.0, 0.0, (0.5, 0.5)).is_none());
+    }
+
+    #[test]
+    fn equirectangular() {
+        let camera = camera(Projection::Equirectangular);
+
+        assert_near(&direction(&camera, 100.0, 50.0), &Vector3::new(0, 0, -1));
+        // Corners lie on the poles, and the left and right edges behind.
+        assert_near(&direction(&camera, 0.0, 0.0), &Vector3::new(0, 1, 0));
+        assert_near(&direction(&camera, 200.0, 100.0), &Vector3::new(0, -1, 0));
+        assert_near(&direction(&camera, 0.0, 50.0), &Vector3::new(0, 0, 1));
+        assert_near(&direction(&camera, 50.0, 50.0), &Vector3::new(-1, 0, 0));
+    }
+
+    #[test]
+    fn clip_range() {
+        let mut camera = camera(Projection::Perspective { fov_v: 90.0 });
+        camera.near_plane = 1.0;
+        camera.far_plane = 10.0;
+
+        let corner = camera.get_ray(0.0, 0.0, (0.5, 0.5)).unwrap();
+        let (near, far) = camera.get_clip_range(&corner);
+        assert!((near - 6f32.sqrt()).abs() < 1e-5);
+        assert!((far - 10.0 * 6f32.sqrt()).abs() < 1e-4);
+    }
+
+    static LENS_SAMPLES: [(f32, f32); 5] =
+        [(0.5, 0.5), (0.0, 0.0), (1.0, 0.25), (0.1, 0.9), (0.75, 0.6)];
+
+    #[test]
+    fn pinhole_without_aperture() {
+        let camera = Camera {
+            focus_distance: 5.0,
+            ..camera(Projection::Perspective { fov_v: 60.0 })
+        };
+        let pinhole = Ray::new(
+            &camera.position,
+            &camera.to_world(&Vector3::new(0.3, -0.2, 1.0)),
+        );
+
+        for lens_sample in LENS_SAMPLES {
+            let ray = camera.get_lens_ray(&Vector3::new(0.3, -0.2, 1.0), lens_sample);
+            assert_near(&ray.origin, &pinhole.origin);
+            assert_near(&ray.direction, &pinhole.direction);
+
+            let ray = camera.get_ray(30.0, 70.0, lens_sample).unwrap();
+            assert_near(&ray.origin, &camera.position);
+            assert_near(&ray.direction, &direction(&camera, 30.0, 70.0));
+        }
+    }
+
+    #[test]
+    fn focus_plane_in_focus() {
+        let pinhole = Camera {
+            focus_distance: 5.0,
+            ..camera(Projection::Perspective { fov_v: 60.0 })
+        };
+        let lens = Camera {
+            aperture_radius: 0.5,
+            focus_distance: 5.0,
+            ..camera(Projection::Perspective { fov_v: 60.0 })
+        };
+
+        for (x, y) in [(100.0, 50.0), (30.0, 70.0), (190.5, 2.5)] {
+            // Where the pixel's pinhole ray crosses the focus plane.
+            let ray = pinhole.get_ray(x, y, (0.5, 0.5)).unwrap();
+            let focused = ray.at(5.0 / Vector3::dot_product(&ray.direction, &pinhole.forward));
+
+            let mut origins = vec![];
+            for lens_sample in LENS_SAMPLES {
+                let ray = lens.get_ray(x, y, lens_sample).unwrap();
+                let to_focused = Vector3::sub(&focused, &ray.origin);
+
+                // The ray passes through the focused point.
+                assert_near(&Vector3::normalize(&to_focused), &ray.direction);
+                origins.push(ray.origin);
+            }
+
+            // Spread over the lens rather than all leaving its center.
+            assert!(origins
+                .iter()
+                .any(|origin| Vector3::magnitude(&Vector3::sub(origin, &lens.position)) > 0.1));
+        }
     }
 }
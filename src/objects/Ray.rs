@@ -19,6 +19,9 @@ impl Ray {
     }
 
     pub fn at(&self, t: f32) -> Vector3 {
-        return Vector3::add(&self.origin, &Vector3::scalar_multiplication(&self.direction, t));
+        return Vector3::add(
+            &self.origin,
+            &Vector3::scalar_multiplication(&self.direction, t),
+        );
     }
 }
@@ -1,34 +1,57 @@
+use std::f32::consts::PI;
 use std::fmt;
+use std::sync::Arc;
 
+use super::material::Material;
 use super::object3d::Object3D;
 use super::ray::Ray;
-use crate::tools::color_tools::Color;
+use crate::tools::aabb::Aabb;
 use crate::tools::intersectable::{Intersectable, Intersection};
+use crate::tools::transform::Transform;
 use crate::tools::vector3::Vector3;
 
+static EPSILON: f32 = 1e-4;
+
 #[derive(Debug)]
 pub struct Sphere {
     pub position: Vector3,
     pub radius: f32,
-    pub color: Color,
+    pub material: Arc<Material>,
+    /// Placement applied on top of `position`, e.g. to squash the sphere
+    /// into an ellipsoid.
+    pub transform: Transform,
 }
 
 impl Sphere {
-    pub fn new(position: Vector3, radius: f32, color: Color) -> Self {
+    pub fn new(position: Vector3, radius: f32, material: Arc<Material>) -> Self {
         return Sphere {
             position,
             radius,
-            color,
+            material,
+            transform: Transform::identity(),
         };
     }
 
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+
+        return self;
+    }
+
     pub fn get_position(&self) -> Vector3 {
         return self.position.clone();
     }
-}
 
-impl Intersectable for Sphere {
-    fn get_intersection(&self, ray: &Ray) -> Option<Intersection> {
+    /// Longitude/latitude mapping of a unit normal into [0, 1]².
+    fn get_uv(normal: &Vector3) -> (f32, f32) {
+        let u = 0.5 + normal.z.atan2(normal.x) / (2.0 * PI);
+        let v = 0.5 - normal.y.clamp(-1.0, 1.0).asin() / PI;
+
+        return (u, v);
+    }
+
+    /// Intersection with a ray in the sphere's local space.
+    fn get_local_intersection(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection> {
         let l = Vector3::sub(&self.position, &ray.origin);
         let tca = Vector3::dot_product(&l, &ray.get_direction());
         let l2 = Vector3::magnitude(&l).powi(2);
@@ -48,12 +71,45 @@ impl Intersectable for Sphere {
         let t0 = tca - diff;
         let t1 = tca + diff;
 
-        let distance = t0.min(t1);
+        // Nearest root inside the range; when the origin is inside the sphere
+        // t0 is behind it and the exit point t1 is the hit.
+        let t_min = t_min.max(EPSILON);
+        let distance = if t0 > t_min && t0 < t_max {
+            t0
+        } else if t1 > t_min && t1 < t_max {
+            t1
+        } else {
+            return None;
+        };
+        let outward_normal = Vector3::normalize(&Vector3::sub(&ray.at(distance), &self.position));
 
-        return Some(Intersection {
+        return Some(Intersection::new(
+            ray,
             distance,
-            object: Object3D::Sphere(self),
-        });
+            outward_normal,
+            Sphere::get_uv(&outward_normal),
+            Object3D::Sphere(self),
+        ));
+    }
+}
+
+impl Intersectable for Sphere {
+    fn get_intersection(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection> {
+        return self
+            .transform
+            .intersect(ray, t_min, t_max, |local_ray, t_min, t_max| {
+                self.get_local_intersection(local_ray, t_min, t_max)
+            });
+    }
+
+    fn get_bounds(&self) -> Option<Aabb> {
+        let extent = Vector3::new(self.radius, self.radius, self.radius);
+        let bounds = Aabb::new(
+            Vector3::sub(&self.position, &extent),
+            Vector3::add(&self.position, &extent),
+        );
+
+        return Some(self.transform.bounds_to_world(&bounds));
     }
 }
 
@@ -61,8 +117,8 @@ impl fmt::Display for Sphere {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Sphere: {{\n  position: {},\n  radius: {},\n  color: {:?}\n}}",
-            self.position, self.radius, self.color
+            "Sphere: {{\n  position: {},\n  radius: {},\n  material: {:?}\n}}",
+            self.position, self.radius, self.material
         )
     }
 }
@@ -1,38 +1,74 @@
 use std::fmt;
+use std::sync::Arc;
 
 use crate::tools::{
-    color_tools::Color,
+    aabb::Aabb,
     intersectable::{Intersectable, Intersection},
+    matrix4::Matrix4,
+    transform::Transform,
     vector3::Vector3,
 };
 
-use super::object3d::Object3D;
+use super::{material::Material, object3d::Object3D};
 
 static EPSILON: f32 = 0.0000000000001;
 
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Debug)]
 pub struct Triangle {
     pub vertices: [Vector3; 3],
+    /// Per-vertex normals for smooth shading; all zero when the triangle is
+    /// flat shaded.
     pub normals: [Vector3; 3],
-    pub color: Color,
+    /// Per-vertex texture coordinates, when the source mesh has them.
+    pub tex_coords: Option<[(f32, f32); 3]>,
+    pub material: Arc<Material>,
 }
 
 impl Triangle {
-    pub fn new(v0: Vector3, v1: Vector3, v2: Vector3, color: Color) -> Triangle {
+    pub fn new(v0: Vector3, v1: Vector3, v2: Vector3, material: Arc<Material>) -> Triangle {
         return Triangle {
             vertices: [v0, v1, v2],
             normals: [Vector3::zero(), Vector3::zero(), Vector3::zero()],
-            color,
+            tex_coords: None,
+            material,
         };
     }
 
+    /// Unit normal on the side from which the vertices appear counter-clockwise.
     pub fn get_normal(&self) -> Vector3 {
         let v = Vector3::sub(&self.vertices[1], &self.vertices[0]);
-        let w = Vector3::sub(&self.vertices[0], &self.vertices[2]);
+        let w = Vector3::sub(&self.vertices[2], &self.vertices[0]);
 
         return Vector3::normalize(&Vector3::cross_product(&v, &w));
     }
 
+    /// Moves the triangle by `transform`. Triangles map exactly onto
+    /// triangles, so the vertices and normals are transformed in place rather
+    /// than keeping the transform around like other objects do.
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.vertices = self.vertices.map(|v| transform.point_to_world(&v));
+
+        if self.has_vertex_normals() {
+            self.normals = self.normals.map(|n| transform.normal_to_world(&n));
+        }
+
+        // Mirroring reverses the winding; swap two corners to keep the face
+        // pointing outwards.
+        if Matrix4::determinant3(&transform.matrix) < 0.0 {
+            self.vertices.swap(1, 2);
+            self.normals.swap(1, 2);
+            if let Some(tex_coords) = &mut self.tex_coords {
+                tex_coords.swap(1, 2);
+            }
+        }
+
+        return self;
+    }
+
+    pub fn has_vertex_normals(&self) -> bool {
+        return self.normals.iter().any(|n| *n != Vector3::zero());
+    }
+
     pub fn get_vertices(&self) -> [Vector3; 3] {
         return self.vertices.clone();
     }
@@ -43,7 +79,12 @@ impl Triangle {
 }
 
 impl Intersectable for Triangle {
-    fn get_intersection(&self, ray: &super::ray::Ray) -> Option<Intersection> {
+    fn get_intersection(
+        &self,
+        ray: &super::ray::Ray,
+        t_min: f32,
+        t_max: f32,
+    ) -> Option<Intersection> {
         let v2v0 = Vector3::sub(&self.vertices[2], &self.vertices[0]);
         let v1v0 = Vector3::sub(&self.vertices[1], &self.vertices[0]);
         let vector_p = Vector3::cross_product(&ray.get_direction(), &v1v0);
@@ -64,10 +105,42 @@ impl Intersectable for Triangle {
 
         let t = inv_det * Vector3::dot_product(&vector_q, &v1v0);
 
-        return Some(Intersection {
-            distance: t,
-            object: Object3D::Triangle(self),
-        });
+        if t.is_nan() || t <= t_min || t >= t_max {
+            return None;
+        }
+
+        // `u` weighs vertex 2 and `v` vertex 1.
+        let uv = match self.tex_coords {
+            Some(tex) => (
+                (1.0 - u - v) * tex[0].0 + v * tex[1].0 + u * tex[2].0,
+                (1.0 - u - v) * tex[0].1 + v * tex[1].1 + u * tex[2].1,
+            ),
+            None => (u, v),
+        };
+
+        let inter = Intersection::new(ray, t, self.get_normal(), uv, Object3D::Triangle(self));
+
+        if !self.has_vertex_normals() {
+            return Some(inter);
+        }
+
+        let interpolated = Vector3::add(
+            &Vector3::add(
+                &Vector3::scalar_multiplication(&self.normals[0], 1.0 - u - v),
+                &Vector3::scalar_multiplication(&self.normals[1], v),
+            ),
+            &Vector3::scalar_multiplication(&self.normals[2], u),
+        );
+
+        if Vector3::magnitude(&interpolated) <= 0.0 {
+            return Some(inter);
+        }
+
+        return Some(inter.with_shading_normal(Vector3::normalize(&interpolated)));
+    }
+
+    fn get_bounds(&self) -> Option<Aabb> {
+        return Some(Aabb::from_points(&self.vertices));
     }
 }
 
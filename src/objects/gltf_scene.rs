@@ -0,0 +1,483 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use gltf::{camera::Projection as GltfProjection, image::Format, mesh::Mode, Document};
+use image::RgbImage;
+
+use super::{
+    camera::{Camera, Projection},
+    instance::Instance,
+    material::Material,
+    model3D::Model3D,
+    texture::Texture,
+    triangle::Triangle,
+};
+use crate::tools::{matrix4::Matrix4, transform::Transform, vector3::Vector3};
+
+/// Image height given to cameras imported from glTF; the width follows from
+/// the camera's aspect ratio.
+static DEFAULT_IMAGE_HEIGHT: usize = 400;
+
+/// A node of the imported scene graph. `instance` and `camera` index into the
+/// owning [`GltfScene`], and are already placed by the node's world transform.
+pub struct GltfNode {
+    pub name: Option<String>,
+    pub instance: Option<usize>,
+    pub camera: Option<usize>,
+    pub children: Vec<GltfNode>,
+}
+
+/// Meshes, cameras and node hierarchy of the default scene of a glTF 2.0
+/// file (`.gltf` with external or embedded buffers, or `.glb`).
+///
+/// Each glTF mesh is loaded once into a shared [`Model3D`], and every node
+/// holding it becomes an [`Instance`] placed by the node's world transform;
+/// nodes scaled to nothing are left out. Perspective and orthographic cameras
+/// become [`Camera`]s placed and oriented like their node.
+pub struct GltfScene {
+    pub nodes: Vec<GltfNode>,
+    pub meshes: Vec<Arc<Model3D>>,
+    pub instances: Vec<Instance>,
+    pub cameras: Vec<Camera>,
+}
+
+impl GltfScene {
+    /// Loads a glTF file along with the buffers and images it references,
+    /// which must be local files or data URIs. Primitives without a material
+    /// use `material`.
+    pub fn load(path: &str, material: Arc<Material>) -> Result<GltfScene, gltf::Error> {
+        let (document, buffers, images) = gltf::import(path)?;
+
+        return Ok(GltfScene::from_document(
+            &document, &buffers, &images, material,
+        ));
+    }
+
+    /// Loads a glTF file held in memory, see [`GltfScene::load`]. Its buffers
+    /// and images must be embedded or data URIs.
+    pub fn load_slice(bytes: &[u8], material: Arc<Material>) -> Result<GltfScene, gltf::Error> {
+        let (document, buffers, images) = gltf::import_slice(bytes)?;
+
+        return Ok(GltfScene::from_document(
+            &document, &buffers, &images, material,
+        ));
+    }
+
+    fn from_document(
+        document: &Document,
+        buffers: &[gltf::buffer::Data],
+        images: &[gltf::image::Data],
+        material: Arc<Material>,
+    ) -> GltfScene {
+        let mut loader = Loader {
+            buffers,
+            images,
+            default_material: material,
+            materials: HashMap::new(),
+            textures: HashMap::new(),
+            meshes: HashMap::new(),
+            scene: GltfScene {
+                nodes: vec![],
+                meshes: vec![],
+                instances: vec![],
+                cameras: vec![],
+            },
+        };
+
+        let roots: Vec<gltf::Node> = match GltfScene::main_scene(document) {
+            Some(scene) => scene.nodes().collect(),
+            None => vec![],
+        };
+
+        for node in roots {
+            let root = loader.load_node(&node, &Matrix4::identity());
+            loader.scene.nodes.push(root);
+        }
+
+        return loader.scene;
+    }
+
+    fn main_scene(document: &Document) -> Option<gltf::Scene> {
+        return document
+            .default_scene()
+            .or_else(|| document.scenes().next());
+    }
+}
+
+struct Loader<'a> {
+    buffers: &'a [gltf::buffer::Data],
+    images: &'a [gltf::image::Data],
+    default_material: Arc<Material>,
+    materials: HashMap<usize, Arc<Material>>,
+    textures: HashMap<usize, Option<Arc<Texture>>>,
+    meshes: HashMap<usize, Option<Arc<Model3D>>>,
+    scene: GltfScene,
+}
+
+impl<'a> Loader<'a> {
+    fn load_node(&mut self, node: &gltf::Node, parent: &Matrix4) -> GltfNode {
+        let world_transform =
+            Matrix4::multiply(parent, &Matrix4::from_columns(node.transform().matrix()));
+
+        let instance = node.mesh().and_then(|mesh| {
+            let mesh = self.get_mesh(&mesh)?;
+            let transform = Transform::from_matrix(world_transform)?;
+            self.scene.instances.push(Instance::new(mesh, transform));
+
+            Some(self.scene.instances.len() - 1)
+        });
+
+        let camera = node.camera().map(|camera| {
+            let camera = Loader::load_camera(&camera, &world_transform);
+            self.scene.cameras.push(camera);
+
+            self.scene.cameras.len() - 1
+        });
+
+        let children = node
+            .children()
+            .map(|child| self.load_node(&child, &world_transform))
+            .collect();
+
+        return GltfNode {
+            name: node.name().map(String::from),
+            instance,
+            camera,
+            children,
+        };
+    }
+
+    /// The shared model of `mesh`, loaded on first use.
+    fn get_mesh(&mut self, mesh: &gltf::Mesh) -> Option<Arc<Model3D>> {
+        if let Some(model) = self.meshes.get(&mesh.index()) {
+            return model.clone();
+        }
+
+        let model = self.load_mesh(mesh).map(Arc::new);
+        self.meshes.insert(mesh.index(), model.clone());
+
+        if let Some(model) = &model {
+            self.scene.meshes.push(model.clone());
+        }
+
+        return model;
+    }
+
+    /// Triangles of every triangle-list primitive of `mesh`, in the mesh's
+    /// space. Primitives without normals are flat shaded, as glTF requires.
+    /// Returns `None` when the mesh has no such primitive.
+    fn load_mesh(&mut self, mesh: &gltf::Mesh) -> Option<Model3D> {
+        let mut triangles = vec![];
+        let mut mesh_material = None;
+
+        for primitive in mesh.primitives() {
+            if primitive.mode() != Mode::Triangles {
+                println!(
+                    "Warning: skipping {:?} primitive of mesh {}",
+                    primitive.mode(),
+                    mesh.index()
+                );
+                continue;
+            }
+
+            let reader = primitive.reader(|buffer| Some(&self.buffers[buffer.index()]));
+            let positions: Vec<Vector3> = match reader.read_positions() {
+                Some(positions) => positions.map(|p| Vector3::new(p[0], p[1], p[2])).collect(),
+                None => continue,
+            };
+            let normals: Option<Vec<Vector3>> = reader.read_normals().map(|normals| {
+                normals
+                    .map(|n| Vector3::normalize(&Vector3::new(n[0], n[1], n[2])))
+                    .collect()
+            });
+
+            let gltf_material = primitive.material();
+            let tex_coord_set = gltf_material
+                .pbr_metallic_roughness()
+                .base_color_texture()
+                .map(|info| info.tex_coord())
+                .unwrap_or(0);
+            // glTF puts the UV origin at the top left of the image.
+            let tex_coords: Option<Vec<(f32, f32)>> = reader
+                .read_tex_coords(tex_coord_set)
+                .map(|tex| tex.into_f32().map(|t| (t[0], 1.0 - t[1])).collect());
+
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..positions.len() as u32).collect(),
+            };
+
+            let material = self.load_material(&gltf_material);
+            mesh_material.get_or_insert_with(|| material.clone());
+
+            for face in indices.chunks_exact(3) {
+                let [a, b, c] = [face[0], face[1], face[2]].map(|i| i as usize);
+
+                if [a, b, c].iter().any(|&i| i >= positions.len()) {
+                    continue;
+                }
+
+                let mut triangle =
+                    Triangle::new(positions[a], positions[b], positions[c], material.clone());
+                triangle.tex_coords = tex_coords
+                    .as_ref()
+                    .filter(|tex| tex.len() == positions.len())
+                    .map(|tex| [tex[a], tex[b], tex[c]]);
+
+                if let Some(normals) = normals.as_ref().filter(|n| n.len() == positions.len()) {
+                    triangle.normals = [normals[a], normals[b], normals[c]];
+                }
+
+                triangles.push(triangle);
+            }
+        }
+
+        return Some(Model3D::new(Vector3::zero(), triangles, mesh_material?));
+    }
+
+    /// Approximates a metallic-roughness material: a Blinn-Phong diffuse base
+    /// blended with a rough metal by `metallicFactor`, and with a pass-through
+    /// layer for the transparency of blended materials.
+    fn load_material(&mut self, material: &gltf::Material) -> Arc<Material> {
+        let index = match material.index() {
+            Some(index) => index,
+            None => return self.default_material.clone(),
+        };
+
+        if let Some(material) = self.materials.get(&index) {
+            return material.clone();
+        }
+
+        let pbr = material.pbr_metallic_roughness();
+        let [r, g, b, alpha] = pbr.base_color_factor();
+        let base_color = Vector3::new(r, g, b);
+        let roughness = pbr.roughness_factor().clamp(0.0, 1.0);
+        let metallic = pbr.metallic_factor().clamp(0.0, 1.0);
+        let texture = pbr
+            .base_color_texture()
+            .and_then(|info| self.load_texture(info.texture().source().index()));
+
+        let mut converted = Arc::new(Material::Diffuse {
+            albedo: base_color,
+            specular: 0.5 * (1.0 - roughness),
+            shininess: Material::roughness_to_shininess(roughness.max(0.05)),
+            texture,
+        });
+
+        if metallic > 0.0 {
+            converted = Material::mix(
+                converted,
+                Arc::new(Material::Metal {
+                    albedo: base_color,
+                    roughness,
+                }),
+                metallic,
+            );
+        }
+
+        if material.alpha_mode() == gltf::material::AlphaMode::Blend && alpha < 1.0 {
+            converted = Material::mix(
+                converted,
+                Arc::new(Material::Dielectric {
+                    tint: Vector3::new(1.0, 1.0, 1.0),
+                    refractive_index: 1.0,
+                }),
+                1.0 - alpha,
+            );
+        }
+
+        self.materials.insert(index, converted.clone());
+
+        return converted;
+    }
+
+    fn load_texture(&mut self, image_index: usize) -> Option<Arc<Texture>> {
+        if let Some(texture) = self.textures.get(&image_index) {
+            return texture.clone();
+        }
+
+        let data = &self.images[image_index];
+        let channels = match data.format {
+            Format::R8 => 1,
+            Format::R8G8 => 2,
+            Format::R8G8B8 => 3,
+            Format::R8G8B8A8 => 4,
+            format => {
+                println!("Warning: unsupported texture format {:?}", format);
+                0
+            }
+        };
+
+        let texture = if channels == 0 {
+            None
+        } else {
+            let image = RgbImage::from_fn(data.width, data.height, |x, y| {
+                let offset = (y * data.width + x) as usize * channels;
+                let pixel = &data.pixels[offset..offset + channels];
+
+                match channels {
+                    1 | 2 => image::Rgb([pixel[0], pixel[0], pixel[0]]),
+                    _ => image::Rgb([pixel[0], pixel[1], pixel[2]]),
+                }
+            });
+
+            Some(Arc::new(Texture::new(image)))
+        };
+
+        self.textures.insert(image_index, texture.clone());
+
+        return texture;
+    }
+
+    fn load_camera(camera: &gltf::Camera, transform: &Matrix4) -> Camera {
+        let (projection, aspect_ratio, near_plane, far_plane) = match camera.projection() {
+            GltfProjection::Perspective(perspective) => (
+                Projection::Perspective {
+                    fov_v: perspective.yfov().to_degrees(),
+                },
+                perspective.aspect_ratio().unwrap_or(1.0),
+                perspective.znear(),
+                perspective.zfar().unwrap_or(f32::INFINITY),
+            ),
+            // xmag and ymag are half the width and height of the view.
+            GltfProjection::Orthographic(orthographic) => (
+                Projection::Orthographic {
+                    height: 2.0 * orthographic.ymag(),
+                },
+                orthographic.xmag() / orthographic.ymag(),
+                orthographic.znear(),
+                orthographic.zfar(),
+            ),
+        };
+
+        let mut converted = Camera {
+            projection,
+            width: (DEFAULT_IMAGE_HEIGHT as f32 * aspect_ratio).round() as usize,
+            height: DEFAULT_IMAGE_HEIGHT,
+            near_plane,
+            far_plane,
+            aperture_radius: 0.0,
+            focus_distance: 1.0,
+            position: Vector3::zero(),
+            right: Vector3::new(1.0, 0.0, 0.0),
+            up: Vector3::new(0.0, 1.0, 0.0),
+            forward: Vector3::new(0.0, 0.0, -1.0),
+        };
+
+        // glTF cameras look down their local -Z axis with +Y up.
+        let eye = Matrix4::transform_point(transform, &Vector3::zero());
+        let forward = Matrix4::transform_vector(transform, &Vector3::new(0.0, 0.0, -1.0));
+        let up = Matrix4::transform_vector(transform, &Vector3::new(0.0, 1.0, 0.0));
+        converted.look_at(eye, Vector3::add(&eye, &forward), up);
+
+        return converted;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{color_tools::Color, test_utils::assert_near};
+
+    /// Two bent triangles without normals, held by a translated parent node
+    /// whose two children place the same mesh, and a camera turned 90° about
+    /// +Y.
+    static SCENE: &str = r#"{
+        "asset": { "version": "2.0" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0, 3] }],
+        "nodes": [
+            { "name": "parent", "translation": [10, 0, 0], "children": [1, 2] },
+            { "mesh": 0, "translation": [0, 1, 0] },
+            { "mesh": 0, "scale": [2, 2, 2] },
+            {
+                "camera": 0,
+                "translation": [0, 0, 5],
+                "rotation": [0, 0.70710678, 0, 0.70710678]
+            }
+        ],
+        "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 } }] }],
+        "cameras": [{
+            "type": "perspective",
+            "perspective": { "yfov": 0.8, "aspectRatio": 1.5, "znear": 0.1, "zfar": 100 }
+        }],
+        "accessors": [{
+            "bufferView": 0,
+            "componentType": 5126,
+            "count": 6,
+            "type": "VEC3",
+            "min": [0, 0, 0],
+            "max": [1, 1, 0.5]
+        }],
+        "bufferViews": [{ "buffer": 0, "byteLength": 72 }],
+        "buffers": [{
+            "byteLength": 72,
+            "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAACAPwAAAAAAAAAAAACAPwAAgD8AAAA/AAAAAAAAgD8AAAAA"
+        }]
+    }"#;
+
+    fn load() -> GltfScene {
+        return GltfScene::load_slice(SCENE.as_bytes(), Material::diffuse(Color::White)).unwrap();
+    }
+
+    #[test]
+    fn mesh_shared_by_nodes() {
+        let scene = load();
+
+        assert_eq!(scene.meshes.len(), 1);
+        assert_eq!(scene.instances.len(), 2);
+        assert!(scene
+            .instances
+            .iter()
+            .all(|instance| Arc::ptr_eq(&instance.mesh, &scene.meshes[0])));
+
+        let parent = &scene.nodes[0];
+        assert_eq!(parent.name.as_deref(), Some("parent"));
+        assert_eq!(parent.children.len(), 2);
+        assert_eq!(parent.children[0].instance, Some(0));
+        assert_eq!(parent.children[1].instance, Some(1));
+    }
+
+    #[test]
+    fn instances_placed_by_world_transform() {
+        let scene = load();
+        let point = Vector3::new(1.0, 0.0, 0.0);
+
+        assert_near(
+            &scene.instances[0].transform.point_to_world(&point),
+            &Vector3::new(11.0, 1.0, 0.0),
+        );
+        assert_near(
+            &scene.instances[1].transform.point_to_world(&point),
+            &Vector3::new(12.0, 0.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn primitives_without_normals_are_flat() {
+        let scene = load();
+        let triangles = &scene.meshes[0].triangles;
+
+        assert_eq!(triangles.len(), 2);
+        assert!(triangles.iter().all(|t| !t.has_vertex_normals()));
+    }
+
+    #[test]
+    fn camera_placed_by_node() {
+        let scene = load();
+        let camera = &scene.cameras[0];
+
+        assert_eq!(scene.nodes[1].camera, Some(0));
+        assert_near(&camera.position, &Vector3::new(0.0, 0.0, 5.0));
+        // The local -Z view direction and +X turn to -X and -Z.
+        assert_near(&camera.forward, &Vector3::new(-1.0, 0.0, 0.0));
+        assert_near(&camera.up, &Vector3::new(0.0, 1.0, 0.0));
+        assert_near(&camera.right, &Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(camera.width, 600);
+        assert_eq!(camera.height, DEFAULT_IMAGE_HEIGHT);
+        assert!(matches!(
+            camera.projection,
+            Projection::Perspective { fov_v } if (fov_v - 0.8f32.to_degrees()).abs() < 1e-4
+        ));
+    }
+}
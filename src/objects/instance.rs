@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use crate::tools::{
+    aabb::Aabb,
+    intersectable::{Intersectable, Intersection},
+    transform::Transform,
+};
+
+use super::{material::Material, model3D::Model3D, object3d::Object3D, ray::Ray};
+
+/// A placed copy of a shared mesh. Copies reuse the mesh's triangles and
+/// acceleration structure, so each one only costs its transform and an
+/// optional material.
+#[derive(Debug, Clone)]
+pub struct Instance {
+    pub mesh: Arc<Model3D>,
+    /// Placement applied on top of the mesh's own transform.
+    pub transform: Transform,
+    /// Replaces the materials of all the mesh's triangles when set.
+    pub material: Option<Arc<Material>>,
+}
+
+impl Instance {
+    pub fn new(mesh: Arc<Model3D>, transform: Transform) -> Self {
+        return Instance {
+            mesh,
+            transform,
+            material: None,
+        };
+    }
+
+    pub fn with_material(mut self, material: Arc<Material>) -> Self {
+        self.material = Some(material);
+
+        return self;
+    }
+}
+
+impl Intersectable for Instance {
+    fn get_intersection(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection> {
+        let mut inter =
+            self.transform
+                .intersect(ray, t_min, t_max, |local_ray, t_min, t_max| {
+                    self.mesh.get_intersection(local_ray, t_min, t_max)
+                })?;
+
+        // Report the instance itself so shading picks up the override; the
+        // triangle hit is still known through `face_index`.
+        if self.material.is_some() {
+            inter.object = Object3D::Instance(self);
+        }
+
+        return Some(inter);
+    }
+
+    fn get_bounds(&self) -> Option<Aabb> {
+        return self
+            .mesh
+            .get_bounds()
+            .map(|bounds| self.transform.bounds_to_world(&bounds));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::triangle::Triangle;
+    use crate::tools::{color_tools::Color, test_utils::assert_near, vector3::Vector3};
+
+    /// Square spanning [-1, 1] on x and y, lifted to z = 1 by the mesh's own
+    /// transform.
+    fn square(material: Arc<Material>) -> Arc<Model3D> {
+        let corner = |x, y| Vector3::new(x, y, 0.0);
+        let triangles = vec![
+            Triangle::new(
+                corner(-1.0, -1.0),
+                corner(1.0, -1.0),
+                corner(1.0, 1.0),
+                material.clone(),
+            ),
+            Triangle::new(
+                corner(-1.0, -1.0),
+                corner(1.0, 1.0),
+                corner(-1.0, 1.0),
+                material.clone(),
+            ),
+        ];
+
+        return Arc::new(Model3D::new(
+            Vector3::new(0.0, 0.0, 1.0),
+            triangles,
+            material,
+        ));
+    }
+
+    #[test]
+    fn hit_in_world_space() {
+        let mesh = square(Material::diffuse(Color::White));
+        // Doubled in size and moved to z = 12, spanning [3, 7] on x.
+        let transform = Transform::identity()
+            .scale(&Vector3::new(2.0, 2.0, 2.0))
+            .translate(&Vector3::new(5.0, 0.0, 10.0));
+        let instance = Instance::new(mesh, transform);
+        let ray = Ray::new(&Vector3::new(6.0, 1.0, 0.0), &Vector3::new(0.0, 0.0, 1.0));
+
+        let hit = instance.get_intersection(&ray, 0.0, f32::INFINITY).unwrap();
+        assert!((hit.distance - 12.0).abs() < 1e-4);
+        assert_near(&hit.point, &Vector3::new(6.0, 1.0, 12.0));
+        assert_near(&hit.normal, &Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(hit.face_index, Some(0));
+
+        // Inside the mesh's own footprint, but outside the placed copy.
+        let miss = Ray::new(&Vector3::new(0.0, 0.0, 0.0), &Vector3::new(0.0, 0.0, 1.0));
+        assert!(instance
+            .get_intersection(&miss, 0.0, f32::INFINITY)
+            .is_none());
+        assert!(instance.get_intersection(&ray, 0.0, 11.9).is_none());
+    }
+
+    #[test]
+    fn material_override() {
+        let mesh_material = Material::diffuse(Color::White);
+        let override_material = Material::metal(Color::DarkOrange, 0.0);
+        let mesh = square(mesh_material.clone());
+        let plain = Instance::new(mesh.clone(), Transform::identity());
+        let overridden =
+            Instance::new(mesh, Transform::identity()).with_material(override_material.clone());
+        let ray = Ray::new(&Vector3::new(0.5, -0.5, 0.0), &Vector3::new(0.0, 0.0, 1.0));
+
+        let hit = overridden
+            .get_intersection(&ray, 0.0, f32::INFINITY)
+            .unwrap();
+        assert!(matches!(hit.object, Object3D::Instance(_)));
+        assert!(std::ptr::eq(hit.object.get_material(), &*override_material));
+        assert_eq!(hit.face_index, Some(0));
+
+        // The shared mesh keeps its own material for other instances.
+        let hit = plain.get_intersection(&ray, 0.0, f32::INFINITY).unwrap();
+        assert!(matches!(hit.object, Object3D::Triangle(_)));
+        assert!(std::ptr::eq(hit.object.get_material(), &*mesh_material));
+    }
+}
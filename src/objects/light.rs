@@ -1,7 +1,253 @@
-use crate::tools::{color_tools::Color, vector3::Vector3};
+use std::f32::consts::PI;
 
-pub struct Light {
+use rand::Rng;
+
+use super::sphere::Sphere;
+use crate::tools::{color_tools::Color, sampling, vector3::Vector3};
+
+/// Direction towards a light picked by [`Light::sample`].
+pub struct LightSample {
+    /// Unit vector from the shaded point towards the light.
     pub direction: Vector3,
-    pub color: Color,
-    pub intensity: f32,
+    pub distance: f32,
+    /// Radiance arriving at the shaded point, before any occlusion.
+    pub radiance: Vector3,
+    /// Solid angle pdf of `direction`; 1 for delta lights.
+    pub pdf: f32,
+    /// Point and directional lights can only be reached by light sampling.
+    pub is_delta: bool,
+}
+
+pub enum Light {
+    /// Infinitely far light. `direction` is the direction in which the light
+    /// travels, so surfaces facing against it are lit.
+    Directional {
+        direction: Vector3,
+        color: Color,
+        intensity: f32,
+    },
+    /// Light emitted from a single point, falling off with the squared distance.
+    Point {
+        position: Vector3,
+        color: Color,
+        intensity: f32,
+    },
+    /// Spherical area light emitting `emission` radiance from its surface.
+    /// Built from emissive spheres in the scene, see [`Light::from_sphere`].
+    Sphere {
+        position: Vector3,
+        radius: f32,
+        emission: Vector3,
+    },
+}
+
+impl Light {
+    pub fn directional(direction: Vector3, color: Color, intensity: f32) -> Self {
+        return Light::Directional {
+            direction: Vector3::normalize(&direction),
+            color,
+            intensity,
+        };
+    }
+
+    pub fn point(position: Vector3, color: Color, intensity: f32) -> Self {
+        return Light::Point {
+            position,
+            color,
+            intensity,
+        };
+    }
+
+    /// Area light matching `sphere` in world space, if its material emits
+    /// anything. Spheres stretched into ellipsoids by their transform give no
+    /// light to sample; they still light the scene when paths hit them.
+    pub fn from_sphere(sphere: &Sphere) -> Option<Self> {
+        let emission = sphere.material.get_emission();
+
+        if Vector3::max_component(&emission) <= 0.0 {
+            return None;
+        }
+
+        let scale = sphere.transform.uniform_scale()?;
+
+        return Some(Light::Sphere {
+            position: sphere.transform.point_to_world(&sphere.position),
+            radius: sphere.radius * scale,
+            emission,
+        });
+    }
+
+    /// Unit vector pointing from `point` towards the light (its center for area
+    /// lights).
+    pub fn get_direction_to(&self, point: &Vector3) -> Vector3 {
+        match self {
+            Light::Directional { direction, .. } => -Vector3::normalize(direction),
+            Light::Point { position, .. } | Light::Sphere { position, .. } => {
+                Vector3::normalize(&Vector3::sub(position, point))
+            }
+        }
+    }
+
+    /// Distance from `point` to the light; directional lights are infinitely far.
+    pub fn get_distance_to(&self, point: &Vector3) -> f32 {
+        match self {
+            Light::Directional { .. } => f32::INFINITY,
+            Light::Point { position, .. } => Vector3::magnitude(&Vector3::sub(position, point)),
+            Light::Sphere {
+                position, radius, ..
+            } => Vector3::magnitude(&Vector3::sub(position, point)) - radius,
+        }
+    }
+
+    /// Radiance arriving at `point`, treating area lights as points at their
+    /// center. Used by the deterministic Whitted integrator.
+    pub fn get_radiance_at(&self, point: &Vector3) -> Vector3 {
+        match self {
+            Light::Directional {
+                color, intensity, ..
+            } => Vector3::scalar_multiplication(&color.to_vector3(), *intensity),
+            Light::Point {
+                position,
+                color,
+                intensity,
+            } => {
+                let distance2 = Vector3::dot_product(
+                    &Vector3::sub(position, point),
+                    &Vector3::sub(position, point),
+                );
+
+                Vector3::scalar_multiplication(&color.to_vector3(), intensity / distance2)
+            }
+            Light::Sphere {
+                position,
+                radius,
+                emission,
+            } => {
+                let distance2 = Vector3::dot_product(
+                    &Vector3::sub(position, point),
+                    &Vector3::sub(position, point),
+                );
+
+                Vector3::scalar_multiplication(emission, PI * radius * radius / distance2)
+            }
+        }
+    }
+
+    /// Samples a direction from `point` towards the light. Returns `None` when
+    /// `point` is inside a sphere light.
+    pub fn sample(&self, point: &Vector3, rng: &mut impl Rng) -> Option<LightSample> {
+        match self {
+            Light::Directional { .. } | Light::Point { .. } => Some(LightSample {
+                direction: self.get_direction_to(point),
+                distance: self.get_distance_to(point),
+                radiance: self.get_radiance_at(point),
+                pdf: 1.0,
+                is_delta: true,
+            }),
+            Light::Sphere {
+                position,
+                radius,
+                emission,
+            } => {
+                let to_center = Vector3::sub(position, point);
+                let distance2 = Vector3::dot_product(&to_center, &to_center);
+
+                if distance2 <= radius * radius {
+                    return None;
+                }
+
+                let one_minus_cos_max = Light::cone_one_minus_cos(*radius, distance2);
+                let axis = Vector3::normalize(&to_center);
+                let direction = sampling::uniform_cone(&axis, one_minus_cos_max, rng);
+
+                // Nearest intersection of the sampled direction with the sphere.
+                let tca = Vector3::dot_product(&to_center, &direction);
+                let d2 = (distance2 - tca * tca).max(0.0);
+                let distance = tca - (radius * radius - d2).max(0.0).sqrt();
+
+                Some(LightSample {
+                    direction,
+                    distance,
+                    radiance: *emission,
+                    pdf: sampling::uniform_cone_pdf(one_minus_cos_max),
+                    is_delta: false,
+                })
+            }
+        }
+    }
+
+    /// Solid angle pdf with which [`Light::sample`] picks a direction from
+    /// `point` that hits the light. Zero for delta lights.
+    pub fn pdf(&self, point: &Vector3) -> f32 {
+        match self {
+            Light::Directional { .. } | Light::Point { .. } => 0.0,
+            Light::Sphere {
+                position, radius, ..
+            } => {
+                let to_center = Vector3::sub(position, point);
+                let distance2 = Vector3::dot_product(&to_center, &to_center);
+
+                if distance2 <= radius * radius {
+                    return 0.0;
+                }
+
+                sampling::uniform_cone_pdf(Light::cone_one_minus_cos(*radius, distance2))
+            }
+        }
+    }
+
+    /// 1 - cos θmax for the cone a sphere subtends from a point at squared
+    /// distance `distance2` from its center.
+    fn cone_one_minus_cos(radius: f32, distance2: f32) -> f32 {
+        let sin2_theta_max = radius * radius / distance2;
+
+        // Small or distant spheres: 1 - cos θ ≈ sin² θ / 2 avoids cancellation.
+        if sin2_theta_max < 1e-3 {
+            return sin2_theta_max / 2.0;
+        }
+
+        return 1.0 - (1.0 - sin2_theta_max).sqrt();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::material::Material;
+    use crate::tools::transform::Transform;
+
+    #[test]
+    fn sphere_light_follows_transform() {
+        let sphere = Sphere::new(
+            Vector3::new(1.0, 0.0, 0.0),
+            0.5,
+            Material::emissive(Color::White, 1.0),
+        )
+        .with_transform(
+            Transform::identity()
+                .scale(&Vector3::new(2.0, 2.0, 2.0))
+                .rotate_euler(&Vector3::new(0.0, 0.0, 90.0))
+                .translate(&Vector3::new(0.0, 0.0, 5.0)),
+        );
+
+        match Light::from_sphere(&sphere) {
+            Some(Light::Sphere {
+                position, radius, ..
+            }) => {
+                assert!(
+                    Vector3::magnitude(&Vector3::sub(&position, &Vector3::new(0, 2, 5))) < 1e-5
+                );
+                assert!((radius - 1.0).abs() < 1e-5);
+            }
+            _ => panic!("expected a sphere light"),
+        }
+    }
+
+    #[test]
+    fn ellipsoid_gives_no_light() {
+        let sphere = Sphere::new(Vector3::zero(), 1.0, Material::emissive(Color::White, 1.0))
+            .with_transform(Transform::identity().scale(&Vector3::new(1.0, 2.0, 1.0)));
+
+        assert!(Light::from_sphere(&sphere).is_none());
+    }
 }
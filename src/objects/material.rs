@@ -0,0 +1,450 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use rand::Rng;
+
+use super::texture::Texture;
+use crate::tools::{color_tools::Color, intersectable::Intersection, sampling, vector3::Vector3};
+
+/// Outgoing direction chosen by [`Material::sample`].
+pub struct BsdfSample {
+    pub direction: Vector3,
+    /// BSDF value times the cosine term, divided by the sampling pdf.
+    pub weight: Vector3,
+    /// Solid angle pdf of `direction`; meaningless when `is_specular`.
+    pub pdf: f32,
+    /// Whether the direction came from a delta lobe (mirror or glass), which
+    /// light sampling cannot reach.
+    pub is_specular: bool,
+}
+
+/// Surface appearance shared by reference between primitives, so the same
+/// geometry can be rendered with different looks. Colors are linear RGB in [0, 1].
+#[derive(Debug, Clone)]
+pub enum Material {
+    /// Lambertian surface with a Blinn-Phong highlight. A `texture`, when
+    /// present, is multiplied into `albedo` at the hit's UV coordinates.
+    Diffuse {
+        albedo: Vector3,
+        specular: f32,
+        shininess: f32,
+        texture: Option<Arc<Texture>>,
+    },
+    /// Conductor whose reflection is tinted by `albedo`. A `roughness` of 0 is
+    /// a perfect mirror; up to 1 it spreads reflections over a normalized
+    /// Phong lobe around the mirror direction, see
+    /// [`Material::roughness_to_shininess`].
+    Metal { albedo: Vector3, roughness: f32 },
+    /// Glass, water and the like: Fresnel-weighted reflection and refraction.
+    Dielectric {
+        tint: Vector3,
+        refractive_index: f32,
+    },
+    /// Light-emitting surface.
+    Emissive { emission: Vector3 },
+    /// Linear blend of two materials, `factor` being the weight of `second`.
+    Mix {
+        first: Arc<Material>,
+        second: Arc<Material>,
+        factor: f32,
+    },
+}
+
+impl Material {
+    pub fn diffuse(color: Color) -> Arc<Material> {
+        return Arc::new(Material::Diffuse {
+            albedo: color.to_vector3(),
+            specular: 0.5,
+            shininess: 32.0,
+            texture: None,
+        });
+    }
+
+    pub fn metal(color: Color, roughness: f32) -> Arc<Material> {
+        return Arc::new(Material::Metal {
+            albedo: color.to_vector3(),
+            roughness: roughness.clamp(0.0, 1.0),
+        });
+    }
+
+    pub fn dielectric(refractive_index: f32) -> Arc<Material> {
+        return Arc::new(Material::Dielectric {
+            tint: Vector3::new(1.0, 1.0, 1.0),
+            refractive_index,
+        });
+    }
+
+    pub fn emissive(color: Color, strength: f32) -> Arc<Material> {
+        return Arc::new(Material::Emissive {
+            emission: Vector3::scalar_multiplication(&color.to_vector3(), strength),
+        });
+    }
+
+    pub fn mix(first: Arc<Material>, second: Arc<Material>, factor: f32) -> Arc<Material> {
+        return Arc::new(Material::Mix {
+            first,
+            second,
+            factor: factor.clamp(0.0, 1.0),
+        });
+    }
+
+    /// Blinn-Phong exponent equivalent to a metal roughness.
+    pub fn roughness_to_shininess(roughness: f32) -> f32 {
+        return 2.0 / roughness.max(1e-3).powi(4) - 2.0;
+    }
+
+    /// Inverse of [`Material::roughness_to_shininess`].
+    pub fn shininess_to_roughness(shininess: f32) -> f32 {
+        return (2.0 / (shininess.max(0.0) + 2.0)).powf(0.25);
+    }
+
+    /// Schlick's approximation of the Fresnel reflectance between air and a
+    /// medium of index `ior`. Zero for an index of 1, where there is no
+    /// interface to reflect off, such as the pass-through layer of a
+    /// partially transparent material.
+    pub fn schlick(cosine: f32, ior: f32) -> f32 {
+        if ior == 1.0 {
+            return 0.0;
+        }
+
+        let r0 = ((1.0 - ior) / (1.0 + ior)).powi(2);
+
+        return r0 + (1.0 - r0) * (1.0 - cosine).powi(5);
+    }
+
+    /// Diffuse color at the hit, with the texture applied. Zero for materials
+    /// without a diffuse component.
+    pub fn get_albedo(&self, inter: &Intersection) -> Vector3 {
+        match self {
+            Material::Diffuse {
+                albedo, texture, ..
+            } => match texture {
+                Some(texture) => {
+                    Vector3::component_multiplication(albedo, &texture.get_color(inter.uv))
+                }
+                None => *albedo,
+            },
+            _ => Vector3::zero(),
+        }
+    }
+
+    /// Radiance emitted by the surface.
+    pub fn get_emission(&self) -> Vector3 {
+        match self {
+            Material::Emissive { emission } => *emission,
+            Material::Mix {
+                first,
+                second,
+                factor,
+            } => Vector3::add(
+                &Vector3::scalar_multiplication(&first.get_emission(), 1.0 - factor),
+                &Vector3::scalar_multiplication(&second.get_emission(), *factor),
+            ),
+            _ => Vector3::zero(),
+        }
+    }
+
+    /// Importance samples the direction a path continues in after arriving along
+    /// `direction`. Returns `None` when the path is absorbed. Diffuse surfaces
+    /// are treated as purely Lambertian here, without the Blinn-Phong lobe.
+    pub fn sample(
+        &self,
+        direction: &Vector3,
+        inter: &Intersection,
+        rng: &mut impl Rng,
+    ) -> Option<BsdfSample> {
+        let normal = &inter.shading_normal;
+
+        match self {
+            Material::Diffuse { .. } => {
+                let scattered = sampling::cosine_hemisphere(normal, rng);
+
+                Some(BsdfSample {
+                    direction: scattered,
+                    weight: self.get_albedo(inter),
+                    pdf: Vector3::dot_product(&scattered, normal).max(0.0) / PI,
+                    is_specular: false,
+                })
+            }
+            Material::Metal { albedo, roughness } => {
+                let reflected = Vector3::reflect(direction, normal);
+
+                if *roughness <= 0.0 {
+                    if Vector3::dot_product(&reflected, &inter.normal) <= 0.0 {
+                        return None;
+                    }
+
+                    return Some(BsdfSample {
+                        direction: reflected,
+                        weight: *albedo,
+                        pdf: 0.0,
+                        is_specular: true,
+                    });
+                }
+
+                let scattered = sampling::phong_lobe(
+                    &reflected,
+                    Material::roughness_to_shininess(*roughness),
+                    rng,
+                );
+                let pdf = self.pdf(direction, &scattered, inter);
+
+                // Directions below the surface are absorbed.
+                if pdf <= 0.0 || Vector3::dot_product(&scattered, &inter.normal) <= 0.0 {
+                    return None;
+                }
+
+                Some(BsdfSample {
+                    direction: scattered,
+                    weight: Vector3::scalar_multiplication(
+                        &self.evaluate(direction, &scattered, inter),
+                        1.0 / pdf,
+                    ),
+                    pdf,
+                    is_specular: false,
+                })
+            }
+            Material::Dielectric {
+                tint,
+                refractive_index,
+            } => {
+                let eta = if inter.front_face {
+                    1.0 / refractive_index
+                } else {
+                    *refractive_index
+                };
+                let reflection = BsdfSample {
+                    direction: Vector3::reflect(direction, normal),
+                    weight: Vector3::new(1.0, 1.0, 1.0),
+                    pdf: 0.0,
+                    is_specular: true,
+                };
+
+                // Choose between reflection and refraction with probability equal
+                // to the Fresnel reflectance, so the weights need no rescaling.
+                match Vector3::refract(direction, normal, eta) {
+                    Some(refracted) => {
+                        let cos_i = -Vector3::dot_product(direction, normal);
+                        let cos_t = -Vector3::dot_product(&refracted, normal);
+                        let cosine = if eta > 1.0 { cos_t } else { cos_i };
+
+                        if rng.gen::<f32>() < Material::schlick(cosine, *refractive_index) {
+                            Some(reflection)
+                        } else {
+                            Some(BsdfSample {
+                                direction: Vector3::normalize(&refracted),
+                                weight: *tint,
+                                pdf: 0.0,
+                                is_specular: true,
+                            })
+                        }
+                    }
+                    None => Some(reflection),
+                }
+            }
+            Material::Emissive { .. } => None,
+            Material::Mix {
+                first,
+                second,
+                factor,
+            } => {
+                let sample = if rng.gen::<f32>() < *factor {
+                    second.sample(direction, inter, rng)?
+                } else {
+                    first.sample(direction, inter, rng)?
+                };
+
+                if sample.is_specular {
+                    return Some(sample);
+                }
+
+                // A non-delta direction could have come from either lobe, so
+                // weight it by the full mixture.
+                let pdf = self.pdf(direction, &sample.direction, inter);
+
+                if pdf <= 0.0 {
+                    return None;
+                }
+
+                Some(BsdfSample {
+                    weight: Vector3::scalar_multiplication(
+                        &self.evaluate(direction, &sample.direction, inter),
+                        1.0 / pdf,
+                    ),
+                    pdf,
+                    ..sample
+                })
+            }
+        }
+    }
+
+    /// BSDF times cosine for light leaving towards `to_light`, for a path
+    /// arriving along `direction`. Excludes delta lobes, which only
+    /// [`Material::sample`] can produce.
+    pub fn evaluate(
+        &self,
+        direction: &Vector3,
+        to_light: &Vector3,
+        inter: &Intersection,
+    ) -> Vector3 {
+        let cosine = Vector3::dot_product(to_light, &inter.shading_normal);
+        let above = cosine > 0.0 && Vector3::dot_product(to_light, &inter.normal) > 0.0;
+
+        match self {
+            Material::Mix {
+                first,
+                second,
+                factor,
+            } => Vector3::add(
+                &Vector3::scalar_multiplication(
+                    &first.evaluate(direction, to_light, inter),
+                    1.0 - factor,
+                ),
+                &Vector3::scalar_multiplication(
+                    &second.evaluate(direction, to_light, inter),
+                    *factor,
+                ),
+            ),
+            _ if !above => Vector3::zero(),
+            Material::Diffuse { .. } => {
+                Vector3::scalar_multiplication(&self.get_albedo(inter), cosine / PI)
+            }
+            Material::Metal { albedo, roughness } if *roughness > 0.0 => {
+                // Normalized Phong lobe around the mirror direction.
+                let shininess = Material::roughness_to_shininess(*roughness);
+                let cos_alpha = Vector3::dot_product(
+                    to_light,
+                    &Vector3::reflect(direction, &inter.shading_normal),
+                );
+
+                if cos_alpha <= 0.0 {
+                    return Vector3::zero();
+                }
+
+                Vector3::scalar_multiplication(
+                    albedo,
+                    (shininess + 2.0) / (2.0 * PI) * cos_alpha.powf(shininess) * cosine,
+                )
+            }
+            _ => Vector3::zero(),
+        }
+    }
+
+    /// Solid angle pdf with which [`Material::sample`] picks `to_light` for a
+    /// path arriving along `direction`, excluding delta lobes.
+    pub fn pdf(&self, direction: &Vector3, to_light: &Vector3, inter: &Intersection) -> f32 {
+        match self {
+            Material::Diffuse { .. } => {
+                Vector3::dot_product(to_light, &inter.shading_normal).max(0.0) / PI
+            }
+            Material::Metal { roughness, .. } if *roughness > 0.0 => sampling::phong_lobe_pdf(
+                Vector3::dot_product(
+                    to_light,
+                    &Vector3::reflect(direction, &inter.shading_normal),
+                ),
+                Material::roughness_to_shininess(*roughness),
+            ),
+            Material::Mix {
+                first,
+                second,
+                factor,
+            } => {
+                (1.0 - factor) * first.pdf(direction, to_light, inter)
+                    + factor * second.pdf(direction, to_light, inter)
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// True when the material only has delta lobes (or does not scatter at all),
+    /// so sampling lights from it is pointless.
+    pub fn is_specular(&self) -> bool {
+        match self {
+            Material::Diffuse { .. } => false,
+            Material::Metal { roughness, .. } => *roughness <= 0.0,
+            Material::Mix { first, second, .. } => first.is_specular() && second.is_specular(),
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    use super::*;
+    use crate::objects::{ray::Ray, sphere::Sphere};
+    use crate::tools::intersectable::Intersectable;
+
+    /// Samples `material` on a unit sphere at the origin, hit by `ray`.
+    fn samples(material: Arc<Material>, ray: &Ray, count: usize) -> Vec<BsdfSample> {
+        let sphere = Sphere::new(Vector3::zero(), 1.0, material.clone());
+        let inter = sphere.get_intersection(ray, 0.0, f32::INFINITY).unwrap();
+        let mut rng = SmallRng::seed_from_u64(1);
+
+        return (0..count)
+            .map(|_| material.sample(&ray.direction, &inter, &mut rng).unwrap())
+            .collect();
+    }
+
+    #[test]
+    fn index_of_one_passes_through() {
+        // Close to grazing, where Schlick's term alone would reflect half the
+        // light.
+        let ray = Ray::new(&Vector3::new(0.99, 0.0, -5.0), &Vector3::new(0.0, 0.0, 1.0));
+        let tint = Vector3::new(0.5, 1.0, 1.0);
+
+        let pass_through = Arc::new(Material::Dielectric {
+            tint,
+            refractive_index: 1.0,
+        });
+        for sample in samples(pass_through, &ray, 200) {
+            assert!(Vector3::magnitude(&Vector3::sub(&sample.direction, &ray.direction)) < 1e-5);
+            assert_eq!(sample.weight, tint);
+        }
+
+        let glass = Arc::new(Material::Dielectric {
+            tint,
+            refractive_index: 1.5,
+        });
+        // Reflections glance off away from the sphere's center.
+        assert!(samples(glass, &ray, 200)
+            .iter()
+            .any(|sample| sample.direction.x > 0.0));
+    }
+
+    #[test]
+    fn total_internal_reflection() {
+        let glass = Material::dielectric(1.5);
+        // Leaves the unit sphere through the point above (x, 0, 0), where the
+        // outward normal is the point itself.
+        let exit_samples = |x: f32| {
+            let ray = Ray::new(&Vector3::new(x, 0.0, 0.0), &Vector3::new(0.0, 0.0, 1.0));
+            let normal = Vector3::new(x, 0.0, (1.0 - x * x).sqrt());
+
+            return samples(glass.clone(), &ray, 200)
+                .into_iter()
+                .map(|sample| {
+                    (
+                        Vector3::dot_product(&sample.direction, &normal) > 0.0,
+                        sample,
+                    )
+                })
+                .collect::<Vec<_>>();
+        };
+
+        // At 64 degrees from the normal, past the critical angle of about 42
+        // degrees, everything is reflected back inside.
+        for (escaped, sample) in exit_samples(0.9) {
+            assert!(!escaped);
+            assert_eq!(sample.weight, Vector3::new(1.0, 1.0, 1.0));
+        }
+
+        // At 30 degrees most of the light gets out.
+        let escaped = exit_samples(0.5)
+            .iter()
+            .filter(|(escaped, _)| *escaped)
+            .count();
+        assert!(escaped > 150);
+    }
+}
@@ -1,8 +1,14 @@
 pub mod camera;
+pub mod gltf_scene;
+pub mod instance;
 pub mod light;
+pub mod material;
 pub mod model3D;
 pub mod object3d;
 pub mod plane;
+pub mod ply;
 pub mod ray;
 pub mod sphere;
+pub mod stl;
+pub mod texture;
 pub mod triangle;
@@ -1,95 +1,505 @@
+use std::collections::HashMap;
+use std::error::Error;
 use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
 
-use super::triangle::Triangle;
+use super::{
+    material::Material, ply::PlyError, stl::StlError, texture::Texture, triangle::Triangle,
+};
 use crate::tools::{
-    color_tools::Color,
+    aabb::Aabb,
+    bvh::Bvh,
     intersectable::{Intersectable, Intersection},
+    transform::Transform,
     vector3::Vector3,
 };
-use obj::{Obj, ObjError};
+use obj::{Obj, ObjError, ObjMaterial};
+
+/// Faces meeting at a sharper angle than this keep a hard edge when vertex
+/// normals have to be computed.
+pub static DEFAULT_CREASE_ANGLE: f32 = 60.0;
+
+/// Error loading a mesh with [`Model3D::new_from_file`].
+#[derive(Debug)]
+pub enum ModelError {
+    Obj(ObjError),
+    Ply(PlyError),
+    Stl(StlError),
+    /// The file extension is not one of `obj`, `ply` or `stl`.
+    UnsupportedFormat(String),
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelError::Obj(e) => write!(f, "cannot load OBJ file: {}", e),
+            ModelError::Ply(e) => e.fmt(f),
+            ModelError::Stl(e) => e.fmt(f),
+            ModelError::UnsupportedFormat(path) => {
+                write!(f, "unsupported model file `{}`", path)
+            }
+        }
+    }
+}
+
+impl Error for ModelError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ModelError::Obj(e) => Some(e),
+            ModelError::Ply(e) => Some(e),
+            ModelError::Stl(e) => Some(e),
+            ModelError::UnsupportedFormat(_) => None,
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct Model3D {
-    pub position: Vector3,
+    /// Triangles in the mesh's local space.
     pub triangles: Vec<Triangle>,
-    pub color: Color,
+    pub material: Arc<Material>,
+    /// Placement of the mesh in the world; rays are intersected with the
+    /// triangles in local space.
+    pub transform: Transform,
+    bvh: Bvh,
 }
 
 impl Model3D {
+    /// Loads an OBJ, PLY or STL mesh, picking the format from the file
+    /// extension.
+    pub fn new_from_file(
+        position: Vector3,
+        path: &str,
+        material: Arc<Material>,
+    ) -> Result<Model3D, ModelError> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+
+        return match extension.as_deref() {
+            Some("obj") => {
+                Model3D::new_from_obj_file(position, path, material).map_err(ModelError::Obj)
+            }
+            Some("ply") => {
+                Model3D::new_from_ply_file(position, path, material).map_err(ModelError::Ply)
+            }
+            Some("stl") => {
+                Model3D::new_from_stl_file(position, path, material).map_err(ModelError::Stl)
+            }
+            _ => Err(ModelError::UnsupportedFormat(path.to_string())),
+        };
+    }
+
+    /// Loads every face of an OBJ file, triangulating polygons with more than
+    /// three corners. Faces referencing missing vertices are skipped. Vertex
+    /// normals come from the `vn` records, or are computed for faces without
+    /// them, see [`Model3D::compute_vertex_normals`].
+    ///
+    /// Groups using a material from the file's `mtllib`s get it converted with
+    /// [`Model3D::material_from_mtl`]; the others, and every group when the
+    /// libraries cannot be read, use `material`.
     pub fn new_from_obj_file(
         position: Vector3,
         obj_path: &str,
-        color: Color,
+        material: Arc<Material>,
     ) -> Result<Model3D, ObjError> {
-        let obj = Obj::load(obj_path)?;
+        let mut obj = Obj::load(obj_path)?;
+
+        if let Err(e) = obj.load_mtls() {
+            println!("Warning: {}", e);
+        }
+
+        let data = &obj.data;
         let mut triangles = vec![];
+        let mut materials: HashMap<String, Arc<Material>> = HashMap::new();
+
+        for group in data.objects.iter().flat_map(|object| object.groups.iter()) {
+            let group_material = match &group.material {
+                Some(ObjMaterial::Mtl(mtl)) => materials
+                    .entry(mtl.name.clone())
+                    .or_insert_with(|| Model3D::material_from_mtl(mtl, &obj.path))
+                    .clone(),
+                _ => material.clone(),
+            };
+
+            for polygon in &group.polys {
+                let corners = &polygon.0;
+                let positions: Option<Vec<Vector3>> = corners
+                    .iter()
+                    .map(|corner| {
+                        data.position
+                            .get(corner.0)
+                            .map(|p| Vector3::new(p[0], p[1], p[2]))
+                    })
+                    .collect();
+                let positions = match positions {
+                    Some(positions) => positions,
+                    None => continue,
+                };
+                let tex_coords: Option<Vec<(f32, f32)>> = corners
+                    .iter()
+                    .map(|corner| {
+                        corner
+                            .1
+                            .and_then(|i| data.texture.get(i))
+                            .map(|t| (t[0], t[1]))
+                    })
+                    .collect();
+                let normals: Option<Vec<Vector3>> = corners
+                    .iter()
+                    .map(|corner| {
+                        corner
+                            .2
+                            .and_then(|i| data.normal.get(i))
+                            .map(|n| Vector3::normalize(&Vector3::new(n[0], n[1], n[2])))
+                    })
+                    .collect();
 
-        let mut vtx_triangle = [Vector3::zero(), Vector3::zero(), Vector3::zero()];
+                for [a, b, c] in Model3D::triangulate(&positions) {
+                    let mut triangle = Triangle::new(
+                        positions[a],
+                        positions[b],
+                        positions[c],
+                        group_material.clone(),
+                    );
+                    triangle.tex_coords = tex_coords.as_ref().map(|tex| [tex[a], tex[b], tex[c]]);
 
-        obj.data.position.windows(3).for_each(|vtx| {
-            for i in 0..3 {
-                vtx_triangle[i].x = vtx[i][0];
-                vtx_triangle[i].y = vtx[i][1];
-                vtx_triangle[i].z = vtx[i][2];
+                    if let Some(normals) = &normals {
+                        triangle.normals = [normals[a], normals[b], normals[c]];
+                    }
+
+                    triangles.push(triangle);
+                }
             }
+        }
+
+        Model3D::compute_vertex_normals(&mut triangles, DEFAULT_CREASE_ANGLE);
+
+        return Ok(Model3D::new(position, triangles, material));
+    }
+
+    /// Maps a Wavefront material onto the renderer's materials. `Kd`, `Ks` and
+    /// `Ns` give a Blinn-Phong diffuse surface, textured by `map_Kd` (resolved
+    /// relative to `directory`). The illumination model adds a reflective
+    /// metal layer weighted by `Ks` (illum 3 and 5) or a refractive layer
+    /// with index `Ni` (illum 4, 6, 7 and 9). Dissolve (`d`, or `Tr`) blends
+    /// in that refractive layer, or a straight pass-through one when the
+    /// model does not refract.
+    pub fn material_from_mtl(mtl: &obj::Material, directory: &Path) -> Arc<Material> {
+        let to_vector3 = |c: [f32; 3]| Vector3::new(c[0], c[1], c[2]);
+        let kd = mtl
+            .kd
+            .map(to_vector3)
+            .unwrap_or(Vector3::new(0.8, 0.8, 0.8));
+        let ks = mtl.ks.map(to_vector3).unwrap_or(Vector3::zero());
+        let shininess = mtl.ns.unwrap_or(0.0).max(1.0);
+        let illum = mtl.illum.unwrap_or(2);
+        let opacity = mtl
+            .d
+            .or(mtl.tr.map(|tr| 1.0 - tr))
+            .unwrap_or(1.0)
+            .clamp(0.0, 1.0);
 
-            triangles.push(Triangle::new(
-                vtx_triangle[0].clone(),
-                vtx_triangle[1].clone(),
-                vtx_triangle[2].clone(),
-                color,
-            ))
+        let texture =
+            mtl.map_kd
+                .as_ref()
+                .and_then(|name| match Texture::load(directory.join(name)) {
+                    Ok(texture) => Some(Arc::new(texture)),
+                    Err(e) => {
+                        println!("Warning: cannot load texture {}: {}", name, e);
+                        None
+                    }
+                });
+
+        let diffuse = Arc::new(Material::Diffuse {
+            albedo: kd,
+            specular: if illum >= 2 {
+                Vector3::max_component(&ks)
+            } else {
+                0.0
+            },
+            shininess,
+            texture,
         });
 
-        return Ok(Model3D::new(position, triangles, color));
+        let refracts = matches!(illum, 4 | 6 | 7 | 9);
+        let surface = match illum {
+            3 | 5 if Vector3::max_component(&ks) > 0.0 => Material::mix(
+                diffuse,
+                Arc::new(Material::Metal {
+                    albedo: Vector3::scalar_multiplication(&ks, 1.0 / Vector3::max_component(&ks)),
+                    roughness: Material::shininess_to_roughness(shininess),
+                }),
+                Vector3::max_component(&ks),
+            ),
+            _ => diffuse,
+        };
+
+        // Refracting models without a dissolve are assumed to be clear glass.
+        let transparency = if refracts && opacity >= 1.0 {
+            1.0
+        } else {
+            1.0 - opacity
+        };
+
+        if transparency <= 0.0 {
+            return surface;
+        }
+
+        let transmission = Arc::new(Material::Dielectric {
+            tint: mtl
+                .tf
+                .map(to_vector3)
+                .unwrap_or(Vector3::new(1.0, 1.0, 1.0)),
+            refractive_index: if refracts {
+                mtl.ni.unwrap_or(1.5).max(1.0)
+            } else {
+                1.0
+            },
+        });
+
+        return Material::mix(surface, transmission, transparency);
     }
 
-    pub fn new(position: Vector3, triangles: Vec<Triangle>, color: Color) -> Self {
-        let mut triangles = triangles.clone();
-        Model3D::set_translated_triangles(&position, &mut triangles);
+    /// Fills in the vertex normals of triangles that have none by averaging the
+    /// normals of the faces sharing each vertex position, weighted by face area
+    /// and by the angle at that corner. Faces deviating from the triangle's own
+    /// normal by more than `crease_angle` degrees are left out, which keeps
+    /// sharp edges hard.
+    pub fn compute_vertex_normals(triangles: &mut [Triangle], crease_angle: f32) {
+        // -0.0 and 0.0 are the same position but differ in their bits.
+        let bits = |c: f32| if c == 0.0 { 0u32 } else { c.to_bits() };
+        let key = |v: &Vector3| (bits(v.x), bits(v.y), bits(v.z));
+        let cos_crease = crease_angle.to_radians().cos();
 
-        return Model3D {
-            position,
-            triangles,
-            color,
+        // Face normal scaled by twice the area, and the normalized version.
+        let area_normals: Vec<Vector3> = triangles
+            .iter()
+            .map(|t| {
+                Vector3::cross_product(
+                    &Vector3::sub(&t.vertices[1], &t.vertices[0]),
+                    &Vector3::sub(&t.vertices[2], &t.vertices[0]),
+                )
+            })
+            .collect();
+        let face_normals: Vec<Vector3> = area_normals
+            .iter()
+            .map(|n| {
+                if Vector3::magnitude(n) > 0.0 {
+                    Vector3::normalize(n)
+                } else {
+                    Vector3::zero()
+                }
+            })
+            .collect();
+
+        let mut corners_at: HashMap<(u32, u32, u32), Vec<(usize, usize)>> = HashMap::new();
+        for (t, triangle) in triangles.iter().enumerate() {
+            for (k, vertex) in triangle.vertices.iter().enumerate() {
+                corners_at.entry(key(vertex)).or_default().push((t, k));
+            }
+        }
+
+        let corner_angle = |triangle: &Triangle, k: usize| {
+            let vertex = &triangle.vertices[k];
+            let a = Vector3::sub(&triangle.vertices[(k + 1) % 3], vertex);
+            let b = Vector3::sub(&triangle.vertices[(k + 2) % 3], vertex);
+            let denominator = Vector3::magnitude(&a) * Vector3::magnitude(&b);
+
+            if denominator <= 0.0 {
+                return 0.0;
+            }
+
+            (Vector3::dot_product(&a, &b) / denominator)
+                .clamp(-1.0, 1.0)
+                .acos()
         };
+
+        let mut computed: Vec<(usize, [Vector3; 3])> = Vec::new();
+
+        for (t, triangle) in triangles.iter().enumerate() {
+            if triangle.has_vertex_normals() {
+                continue;
+            }
+
+            let mut normals = [face_normals[t]; 3];
+
+            for (k, vertex) in triangle.vertices.iter().enumerate() {
+                let mut sum = Vector3::zero();
+
+                for &(other, other_k) in &corners_at[&key(vertex)] {
+                    if Vector3::dot_product(&face_normals[t], &face_normals[other]) < cos_crease {
+                        continue;
+                    }
+
+                    let weight = corner_angle(&triangles[other], other_k);
+                    sum = Vector3::add(
+                        &sum,
+                        &Vector3::scalar_multiplication(&area_normals[other], weight),
+                    );
+                }
+
+                if Vector3::magnitude(&sum) > 0.0 {
+                    normals[k] = Vector3::normalize(&sum);
+                }
+            }
+
+            computed.push((t, normals));
+        }
+
+        for (t, normals) in computed {
+            triangles[t].normals = normals;
+        }
     }
 
-    fn set_translated_triangles(position: &Vector3, triangles: &mut Vec<Triangle>) {
-        for triangle in triangles {
-            let mut new_vertices: [Vector3; 3] =
-                [Vector3::zero(), Vector3::zero(), Vector3::zero()];
+    /// Splits a planar polygon into triangles by ear clipping, returning indices
+    /// into `vertices`. Keeps the polygon's winding and handles concave outlines;
+    /// falls back to a fan for degenerate input.
+    pub fn triangulate(vertices: &[Vector3]) -> Vec<[usize; 3]> {
+        let count = vertices.len();
+
+        if count < 3 {
+            return vec![];
+        } else if count == 3 {
+            return vec![[0, 1, 2]];
+        }
+
+        // Newell's method gives a robust normal for the polygon's winding.
+        let mut normal = Vector3::zero();
+        for i in 0..count {
+            let current = &vertices[i];
+            let next = &vertices[(i + 1) % count];
+            normal.x += (current.y - next.y) * (current.z + next.z);
+            normal.y += (current.z - next.z) * (current.x + next.x);
+            normal.z += (current.x - next.x) * (current.y + next.y);
+        }
 
-            for (v, vertex) in triangle.get_vertices().iter().enumerate() {
-                new_vertices[v].x = vertex.x + position.x;
-                new_vertices[v].y = vertex.y + position.y;
-                new_vertices[v].z = vertex.z + position.z;
+        let mut remaining: Vec<usize> = (0..count).collect();
+        let mut triangles = Vec::with_capacity(count - 2);
+
+        if Vector3::magnitude(&normal) > 0.0 {
+            while remaining.len() > 3 {
+                let size = remaining.len();
+                let ear = (0..size).find(|&i| {
+                    let a = remaining[(i + size - 1) % size];
+                    let b = remaining[i];
+                    let c = remaining[(i + 1) % size];
+
+                    Model3D::is_ear(vertices, &normal, &remaining, a, b, c)
+                });
+
+                match ear {
+                    Some(i) => {
+                        triangles.push([
+                            remaining[(i + size - 1) % size],
+                            remaining[i],
+                            remaining[(i + 1) % size],
+                        ]);
+                        remaining.remove(i);
+                    }
+                    None => break,
+                }
             }
+        }
+
+        for i in 1..remaining.len() - 1 {
+            triangles.push([remaining[0], remaining[i], remaining[i + 1]]);
+        }
+
+        return triangles;
+    }
+
+    /// Whether corner `b`, between `a` and `c`, is convex and its triangle holds
+    /// no other remaining vertex.
+    fn is_ear(
+        vertices: &[Vector3],
+        normal: &Vector3,
+        remaining: &[usize],
+        a: usize,
+        b: usize,
+        c: usize,
+    ) -> bool {
+        let edge_side = |from: usize, to: usize, p: &Vector3| {
+            let edge = Vector3::sub(&vertices[to], &vertices[from]);
+            let offset = Vector3::sub(p, &vertices[from]);
+
+            Vector3::dot_product(&Vector3::cross_product(&edge, &offset), normal)
+        };
+
+        if edge_side(a, b, &vertices[c]) <= 0.0 {
+            return false;
+        }
+
+        return remaining.iter().all(|&i| {
+            if i == a || i == b || i == c {
+                return true;
+            }
+
+            let p = &vertices[i];
+
+            edge_side(a, b, p) < 0.0 || edge_side(b, c, p) < 0.0 || edge_side(c, a, p) < 0.0
+        });
+    }
+
+    /// Builds the mesh's acceleration structure and places it at `position`.
+    pub fn new(position: Vector3, triangles: Vec<Triangle>, material: Arc<Material>) -> Self {
+        let bounds: Vec<Aabb> = triangles
+            .iter()
+            .map(|triangle| Aabb::from_points(&triangle.vertices))
+            .collect();
+
+        return Model3D {
+            bvh: Bvh::new(&bounds),
+            triangles,
+            material,
+            transform: Transform::translation(&position),
+        };
+    }
 
-            triangle.set_vertices(new_vertices);
+    /// Replaces the material of every triangle of the mesh.
+    pub fn with_material(mut self, material: Arc<Material>) -> Self {
+        for triangle in &mut self.triangles {
+            triangle.material = material.clone();
         }
+        self.material = material;
+
+        return self;
+    }
+
+    /// Replaces the placement of the mesh, including the translation given to
+    /// [`Model3D::new`].
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+
+        return self;
     }
 }
 
 impl Intersectable for Model3D {
-    fn get_intersection(&self, ray: &super::ray::Ray) -> Option<Intersection> {
-        self.triangles
-            .iter()
-            .fold(None, |acc: Option<Intersection>, triangle| {
-                match triangle.get_intersection(&ray) {
-                    Some(curr) => match &acc {
-                        Some(prev) => {
-                            if curr.distance < prev.distance {
-                                return Some(curr.clone());
-                            } else {
-                                return acc;
-                            }
-                        }
-                        None => return Some(curr.clone()),
-                    },
-                    None => return acc,
-                }
-            })
+    fn get_intersection(
+        &self,
+        ray: &super::ray::Ray,
+        t_min: f32,
+        t_max: f32,
+    ) -> Option<Intersection> {
+        return self
+            .transform
+            .intersect(ray, t_min, t_max, |local_ray, t_min, t_max| {
+                self.bvh
+                    .get_intersection(local_ray, t_min, t_max, |index, t_max| {
+                        let mut inter =
+                            self.triangles[index].get_intersection(local_ray, t_min, t_max)?;
+                        inter.face_index = Some(index);
+
+                        Some(inter)
+                    })
+            });
+    }
+
+    fn get_bounds(&self) -> Option<Aabb> {
+        return Some(self.transform.bounds_to_world(&self.bvh.get_bounds()));
     }
 }
 
@@ -103,3 +513,156 @@ impl fmt::Display for Model3D {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{color_tools::Color, test_utils::assert_near};
+
+    fn polygon(points: &[(f32, f32)]) -> Vec<Vector3> {
+        return points
+            .iter()
+            .map(|&(x, y)| Vector3::new(x, y, 0.0))
+            .collect();
+    }
+
+    fn area(a: &Vector3, b: &Vector3, c: &Vector3) -> f32 {
+        return Vector3::cross_product(&Vector3::sub(b, a), &Vector3::sub(c, a)).z / 2.0;
+    }
+
+    /// Even-odd test of whether `p` lies inside the outline.
+    fn inside(outline: &[Vector3], p: &Vector3) -> bool {
+        let mut inside = false;
+
+        for i in 0..outline.len() {
+            let a = &outline[i];
+            let b = &outline[(i + 1) % outline.len()];
+
+            if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+                inside = !inside;
+            }
+        }
+
+        return inside;
+    }
+
+    /// Checks that `triangles` tile the counter-clockwise `outline`: n − 2 of
+    /// them, all counter-clockwise, inside it and covering its area.
+    fn assert_triangulates(outline: &[Vector3], triangles: &[[usize; 3]]) {
+        assert_eq!(triangles.len(), outline.len() - 2);
+
+        let outline_area: f32 = (1..outline.len() - 1)
+            .map(|i| area(&outline[0], &outline[i], &outline[i + 1]))
+            .sum();
+        let mut total = 0.0;
+
+        for &[a, b, c] in triangles {
+            let [a, b, c] = [&outline[a], &outline[b], &outline[c]];
+            let triangle_area = area(a, b, c);
+            assert!(triangle_area > 0.0, "triangle winds clockwise");
+            total += triangle_area;
+
+            // The centroid, and points near each corner, must be inside.
+            for weights in [
+                [1.0, 1.0, 1.0],
+                [4.0, 1.0, 1.0],
+                [1.0, 4.0, 1.0],
+                [1.0, 1.0, 4.0],
+            ] {
+                let point = Vector3::scalar_multiplication(
+                    &Vector3::add(
+                        &Vector3::add(
+                            &Vector3::scalar_multiplication(a, weights[0]),
+                            &Vector3::scalar_multiplication(b, weights[1]),
+                        ),
+                        &Vector3::scalar_multiplication(c, weights[2]),
+                    ),
+                    1.0 / weights.iter().sum::<f32>(),
+                );
+                assert!(inside(outline, &point), "triangle outside the outline");
+            }
+        }
+
+        assert!((total - outline_area).abs() < 1e-5);
+    }
+
+    #[test]
+    fn concave_quad() {
+        // Arrowhead with its reflex corner at index 1, which a fan from
+        // index 0 would get wrong.
+        let outline = polygon(&[(2.0, 1.0), (0.5, 1.0), (0.0, 3.0), (0.0, 0.0)]);
+
+        assert_triangulates(&outline, &Model3D::triangulate(&outline));
+    }
+
+    #[test]
+    fn concave_l_shape() {
+        let outline = polygon(&[
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 3.0),
+            (0.0, 3.0),
+        ]);
+
+        assert_triangulates(&outline, &Model3D::triangulate(&outline));
+    }
+
+    #[test]
+    fn clockwise_winding_kept() {
+        let mut outline = polygon(&[
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 3.0),
+            (0.0, 3.0),
+        ]);
+        outline.reverse();
+
+        let triangles = Model3D::triangulate(&outline);
+        assert_eq!(triangles.len(), 4);
+        for [a, b, c] in triangles {
+            assert!(area(&outline[a], &outline[b], &outline[c]) < 0.0);
+        }
+    }
+
+    #[test]
+    fn collinear_polygon() {
+        let outline = polygon(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0), (4.0, 0.0)]);
+        let triangles = Model3D::triangulate(&outline);
+
+        // No area to clip ears from: a fan over the indices, without panicking.
+        assert_eq!(triangles.len(), 3);
+        assert!(triangles.iter().flatten().all(|&i| i < outline.len()));
+    }
+
+    #[test]
+    fn normals_shared_across_signed_zero() {
+        // A shallow fold along x = 0, stored as -0.0 on one side.
+        let material = Material::diffuse(Color::White);
+        let mut triangles = [
+            Triangle::new(
+                Vector3::new(-0.0, 0.0, 0.0),
+                Vector3::new(-1.0, 0.5, 0.3),
+                Vector3::new(-0.0, 1.0, 0.0),
+                material.clone(),
+            ),
+            Triangle::new(
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(0.0, 1.0, 0.0),
+                Vector3::new(1.0, 0.5, 0.3),
+                material,
+            ),
+        ];
+
+        Model3D::compute_vertex_normals(&mut triangles, DEFAULT_CREASE_ANGLE);
+
+        let fold = Vector3::new(0.0, 0.0, -1.0);
+        assert_near(&triangles[0].normals[0], &fold);
+        assert_near(&triangles[0].normals[2], &fold);
+        assert_near(&triangles[1].normals[0], &fold);
+        assert_near(&triangles[1].normals[1], &fold);
+    }
+}
@@ -1,20 +1,59 @@
-use crate::tools::intersectable::{Intersectable, Intersection};
+use crate::tools::{
+    aabb::Aabb,
+    intersectable::{Intersectable, Intersection},
+};
 
-use super::{model3D::Model3D, plane::Plane, ray::Ray, sphere::Sphere, triangle::Triangle};
+use super::{
+    instance::Instance, material::Material, model3D::Model3D, plane::Plane, ray::Ray,
+    sphere::Sphere, triangle::Triangle,
+};
 
 #[derive(Debug, Clone)]
 pub enum Object3D<'a> {
     Sphere(&'a Sphere),
     Plane(&'a Plane),
     Triangle(&'a Triangle),
+    /// Triangle mesh. Hits report the triangle that was struck as their object,
+    /// and its index in the mesh as the intersection's `face_index`.
+    Model(&'a Model3D),
+    /// Shared mesh placed by its own transform. Hits report the triangle
+    /// struck, or the instance when it overrides the mesh's materials.
+    Instance(&'a Instance),
 }
 
 impl<'a> Intersectable for Object3D<'a> {
-    fn get_intersection(&self, ray: &Ray) -> Option<Intersection<'a>> {
+    fn get_intersection(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection<'a>> {
         match *self {
-            Object3D::Sphere(sphere) => sphere.get_intersection(ray),
-            Object3D::Plane(plane) => plane.get_intersection(ray),
-            Object3D::Triangle(triangle) => triangle.get_intersection(ray),
+            Object3D::Sphere(sphere) => sphere.get_intersection(ray, t_min, t_max),
+            Object3D::Plane(plane) => plane.get_intersection(ray, t_min, t_max),
+            Object3D::Triangle(triangle) => triangle.get_intersection(ray, t_min, t_max),
+            Object3D::Model(model) => model.get_intersection(ray, t_min, t_max),
+            Object3D::Instance(instance) => instance.get_intersection(ray, t_min, t_max),
+        }
+    }
+
+    fn get_bounds(&self) -> Option<Aabb> {
+        match *self {
+            Object3D::Sphere(sphere) => sphere.get_bounds(),
+            Object3D::Plane(plane) => plane.get_bounds(),
+            Object3D::Triangle(triangle) => triangle.get_bounds(),
+            Object3D::Model(model) => model.get_bounds(),
+            Object3D::Instance(instance) => instance.get_bounds(),
+        }
+    }
+}
+
+impl<'a> Object3D<'a> {
+    pub fn get_material(&self) -> &'a Material {
+        match *self {
+            Object3D::Sphere(sphere) => &sphere.material,
+            Object3D::Plane(plane) => &plane.material,
+            Object3D::Triangle(triangle) => &triangle.material,
+            Object3D::Model(model) => &model.material,
+            Object3D::Instance(instance) => match &instance.material {
+                Some(material) => material,
+                None => &instance.mesh.material,
+            },
         }
     }
 }
@@ -1,30 +1,44 @@
+use std::sync::Arc;
+
 use crate::tools::{
-    color_tools::Color,
+    aabb::Aabb,
     intersectable::{Intersectable, Intersection},
+    transform::Transform,
     vector3::Vector3,
 };
 
-use super::{object3d::Object3D, ray::Ray};
+use super::{material::Material, object3d::Object3D, ray::Ray};
+
+static EPSILON: f32 = 1e-4;
 
 #[derive(Debug)]
 pub struct Plane {
     pub origin: Vector3,
     pub normal: Vector3,
-    pub color: Color,
+    pub material: Arc<Material>,
+    /// Placement applied on top of `origin` and `normal`; scaling stretches
+    /// the texture coordinates.
+    pub transform: Transform,
 }
 
 impl Plane {
-    pub fn new(origin: Vector3, normal: Vector3, color: Color) -> Self {
+    pub fn new(origin: Vector3, normal: Vector3, material: Arc<Material>) -> Self {
         return Plane {
             origin,
             normal,
-            color,
+            material,
+            transform: Transform::identity(),
         };
     }
-}
 
-impl Intersectable for Plane {
-    fn get_intersection(&self, ray: &Ray) -> Option<Intersection> {
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+
+        return self;
+    }
+
+    /// Intersection with a ray in the plane's local space.
+    fn get_local_intersection(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection> {
         let denom = Vector3::dot_product(&self.normal, &ray.direction);
         if denom.abs() < 1e-6 {
             return None;
@@ -32,14 +46,34 @@ impl Intersectable for Plane {
 
         let t =
             Vector3::dot_product(&Vector3::sub(&self.origin, &ray.origin), &self.normal) / denom;
+        let t_min = t_min.max(EPSILON);
 
-        if t < 0.0 {
+        if t <= t_min || t >= t_max {
             return None;
         }
 
-        Some(Intersection {
-            distance: t,
-            object: Object3D::Plane(self),
-        })
+        let normal = Vector3::normalize(&self.normal);
+        let (tangent, bitangent) = Vector3::orthonormal_basis(&normal);
+        let local = Vector3::sub(&ray.at(t), &self.origin);
+        let uv = (
+            Vector3::dot_product(&local, &tangent),
+            Vector3::dot_product(&local, &bitangent),
+        );
+
+        Some(Intersection::new(ray, t, normal, uv, Object3D::Plane(self)))
+    }
+}
+
+impl Intersectable for Plane {
+    fn get_intersection(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection> {
+        return self
+            .transform
+            .intersect(ray, t_min, t_max, |local_ray, t_min, t_max| {
+                self.get_local_intersection(local_ray, t_min, t_max)
+            });
+    }
+
+    fn get_bounds(&self) -> Option<Aabb> {
+        None
     }
 }
@@ -0,0 +1,691 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::sync::Arc;
+
+use super::{
+    material::Material,
+    model3D::{Model3D, DEFAULT_CREASE_ANGLE},
+    triangle::Triangle,
+};
+use crate::tools::vector3::Vector3;
+
+#[derive(Debug)]
+pub enum PlyError {
+    Io(io::Error),
+    /// The file does not start with the `ply` magic line.
+    NotPly,
+    UnsupportedFormat(String),
+    InvalidHeader {
+        line: usize,
+        message: String,
+    },
+    /// The vertex element lacks one of the `x`, `y` or `z` properties.
+    MissingVertexProperty(&'static str),
+    UnexpectedEndOfData {
+        element: String,
+    },
+    InvalidValue {
+        element: String,
+        value: String,
+    },
+    IndexOutOfRange {
+        face: usize,
+        index: usize,
+    },
+}
+
+impl fmt::Display for PlyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlyError::Io(e) => write!(f, "cannot read PLY file: {}", e),
+            PlyError::NotPly => write!(f, "not a PLY file: missing `ply` magic line"),
+            PlyError::UnsupportedFormat(format) => {
+                write!(f, "unsupported PLY format `{}`", format)
+            }
+            PlyError::InvalidHeader { line, message } => {
+                write!(f, "invalid PLY header at line {}: {}", line, message)
+            }
+            PlyError::MissingVertexProperty(name) => {
+                write!(f, "PLY vertex element has no `{}` property", name)
+            }
+            PlyError::UnexpectedEndOfData { element } => {
+                write!(f, "PLY data ends in the middle of element `{}`", element)
+            }
+            PlyError::InvalidValue { element, value } => {
+                write!(f, "invalid value `{}` in PLY element `{}`", value, element)
+            }
+            PlyError::IndexOutOfRange { face, index } => {
+                write!(f, "PLY face {} references missing vertex {}", face, index)
+            }
+        }
+    }
+}
+
+impl Error for PlyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PlyError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PlyError {
+    fn from(e: io::Error) -> Self {
+        return PlyError::Io(e);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ScalarType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Option<ScalarType> {
+        return match name {
+            "char" | "int8" => Some(ScalarType::Int8),
+            "uchar" | "uint8" => Some(ScalarType::UInt8),
+            "short" | "int16" => Some(ScalarType::Int16),
+            "ushort" | "uint16" => Some(ScalarType::UInt16),
+            "int" | "int32" => Some(ScalarType::Int32),
+            "uint" | "uint32" => Some(ScalarType::UInt32),
+            "float" | "float32" => Some(ScalarType::Float32),
+            "double" | "float64" => Some(ScalarType::Float64),
+            _ => None,
+        };
+    }
+
+    fn size(&self) -> usize {
+        return match self {
+            ScalarType::Int8 | ScalarType::UInt8 => 1,
+            ScalarType::Int16 | ScalarType::UInt16 => 2,
+            ScalarType::Int32 | ScalarType::UInt32 | ScalarType::Float32 => 4,
+            ScalarType::Float64 => 8,
+        };
+    }
+}
+
+#[derive(Debug)]
+enum Property {
+    Scalar {
+        name: String,
+        value: ScalarType,
+    },
+    List {
+        name: String,
+        count: ScalarType,
+        item: ScalarType,
+    },
+}
+
+impl Property {
+    fn name(&self) -> &str {
+        return match self {
+            Property::Scalar { name, .. } | Property::List { name, .. } => name,
+        };
+    }
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// Reads the values following the header, as text tokens or binary scalars.
+enum Body<'a> {
+    Ascii(std::str::SplitAsciiWhitespace<'a>),
+    Binary {
+        bytes: &'a [u8],
+        offset: usize,
+        big_endian: bool,
+    },
+}
+
+impl<'a> Body<'a> {
+    fn read(&mut self, value: ScalarType, element: &str) -> Result<f64, PlyError> {
+        let end_of_data = || PlyError::UnexpectedEndOfData {
+            element: element.to_string(),
+        };
+
+        match self {
+            Body::Ascii(tokens) => {
+                let token = tokens.next().ok_or_else(end_of_data)?;
+
+                return token.parse::<f64>().map_err(|_| PlyError::InvalidValue {
+                    element: element.to_string(),
+                    value: token.to_string(),
+                });
+            }
+            Body::Binary {
+                bytes,
+                offset,
+                big_endian,
+            } => {
+                let size = value.size();
+                let mut raw = [0u8; 8];
+                raw[..size]
+                    .copy_from_slice(bytes.get(*offset..*offset + size).ok_or_else(end_of_data)?);
+                *offset += size;
+
+                if *big_endian {
+                    raw[..size].reverse();
+                }
+
+                return Ok(match value {
+                    ScalarType::Int8 => raw[0] as i8 as f64,
+                    ScalarType::UInt8 => raw[0] as f64,
+                    ScalarType::Int16 => i16::from_le_bytes([raw[0], raw[1]]) as f64,
+                    ScalarType::UInt16 => u16::from_le_bytes([raw[0], raw[1]]) as f64,
+                    ScalarType::Int32 => {
+                        i32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f64
+                    }
+                    ScalarType::UInt32 => {
+                        u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f64
+                    }
+                    ScalarType::Float32 => {
+                        f32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f64
+                    }
+                    ScalarType::Float64 => f64::from_le_bytes(raw),
+                });
+            }
+        }
+    }
+}
+
+/// One parsed vertex: position plus the optional attributes the file provides.
+struct Vertex {
+    position: Vector3,
+    normal: Option<Vector3>,
+    color: Option<Vector3>,
+    tex_coord: Option<(f32, f32)>,
+}
+
+impl Model3D {
+    /// Loads a PLY mesh in ASCII or binary (little or big endian) format.
+    /// Faces are triangulated like OBJ polygons. Per-vertex normals and
+    /// texture coordinates are kept; per-vertex colors are averaged over each
+    /// face into a diffuse material, replacing `material`. Elements other
+    /// than `vertex` and `face` are skipped.
+    pub fn new_from_ply_file(
+        position: Vector3,
+        ply_path: &str,
+        material: Arc<Material>,
+    ) -> Result<Model3D, PlyError> {
+        return Model3D::new_from_ply_bytes(position, &fs::read(ply_path)?, material);
+    }
+
+    /// Parses the contents of a PLY file, see [`Model3D::new_from_ply_file`].
+    pub fn new_from_ply_bytes(
+        position: Vector3,
+        bytes: &[u8],
+        material: Arc<Material>,
+    ) -> Result<Model3D, PlyError> {
+        let (format, elements, body_start) = parse_header(bytes)?;
+
+        // Invalid UTF-8 in ASCII data surfaces as an unparsable value.
+        let text = match format {
+            Format::Ascii => String::from_utf8_lossy(&bytes[body_start..]),
+            _ => Cow::Borrowed(""),
+        };
+        let mut body = match format {
+            Format::Ascii => Body::Ascii(text.split_ascii_whitespace()),
+            _ => Body::Binary {
+                bytes: &bytes[body_start..],
+                offset: 0,
+                big_endian: format == Format::BinaryBigEndian,
+            },
+        };
+
+        let mut vertices: Vec<Vertex> = vec![];
+        let mut faces: Vec<Vec<usize>> = vec![];
+
+        for element in &elements {
+            match element.name.as_str() {
+                "vertex" => vertices = read_vertices(&mut body, element)?,
+                "face" => faces = read_faces(&mut body, element)?,
+                _ => {
+                    for _ in 0..element.count {
+                        for property in &element.properties {
+                            read_property(&mut body, property, &element.name)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut triangles = vec![];
+        let mut color_materials: HashMap<[u8; 3], Arc<Material>> = HashMap::new();
+
+        for (f, face) in faces.iter().enumerate() {
+            if let Some(&index) = face.iter().find(|&&i| i >= vertices.len()) {
+                return Err(PlyError::IndexOutOfRange { face: f, index });
+            }
+
+            let corners: Vec<&Vertex> = face.iter().map(|&i| &vertices[i]).collect();
+            let positions: Vec<Vector3> = corners.iter().map(|v| v.position).collect();
+
+            for [a, b, c] in Model3D::triangulate(&positions) {
+                let corner = [corners[a], corners[b], corners[c]];
+                let face_material = match corner.map(|v| v.color) {
+                    [Some(ca), Some(cb), Some(cc)] => {
+                        let average = Vector3::scalar_multiplication(
+                            &Vector3::add(&Vector3::add(&ca, &cb), &cc),
+                            1.0 / 3.0,
+                        );
+                        let key = [average.x, average.y, average.z]
+                            .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+
+                        color_materials
+                            .entry(key)
+                            .or_insert_with(|| {
+                                Arc::new(Material::Diffuse {
+                                    albedo: Vector3::scalar_multiplication(
+                                        &Vector3::new(key[0], key[1], key[2]),
+                                        1.0 / 255.0,
+                                    ),
+                                    specular: 0.5,
+                                    shininess: 32.0,
+                                    texture: None,
+                                })
+                            })
+                            .clone()
+                    }
+                    _ => material.clone(),
+                };
+
+                let mut triangle = Triangle::new(
+                    corner[0].position,
+                    corner[1].position,
+                    corner[2].position,
+                    face_material,
+                );
+
+                if let [Some(ta), Some(tb), Some(tc)] = corner.map(|v| v.tex_coord) {
+                    triangle.tex_coords = Some([ta, tb, tc]);
+                }
+
+                if let [Some(na), Some(nb), Some(nc)] = corner.map(|v| v.normal) {
+                    triangle.normals = [na, nb, nc];
+                }
+
+                triangles.push(triangle);
+            }
+        }
+
+        Model3D::compute_vertex_normals(&mut triangles, DEFAULT_CREASE_ANGLE);
+
+        return Ok(Model3D::new(position, triangles, material));
+    }
+}
+
+/// Parses the header, returning the format, the declared elements and the
+/// byte offset at which their data starts.
+fn parse_header(bytes: &[u8]) -> Result<(Format, Vec<Element>, usize), PlyError> {
+    let mut offset = 0;
+    let mut line_number = 0;
+    let mut format = None;
+    let mut elements: Vec<Element> = vec![];
+
+    loop {
+        let end = bytes[offset..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map(|p| offset + p)
+            .ok_or(PlyError::InvalidHeader {
+                line: line_number + 1,
+                message: "missing `end_header`".to_string(),
+            })?;
+        let line = String::from_utf8_lossy(&bytes[offset..end]);
+        let line = line.trim();
+        offset = end + 1;
+        line_number += 1;
+
+        let invalid = |message: &str| PlyError::InvalidHeader {
+            line: line_number,
+            message: message.to_string(),
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        if line_number == 1 {
+            if line != "ply" {
+                return Err(PlyError::NotPly);
+            }
+            continue;
+        }
+
+        match words.as_slice() {
+            [] | ["comment", ..] | ["obj_info", ..] => {}
+            ["format", name, _version] => {
+                format = Some(match *name {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::BinaryLittleEndian,
+                    "binary_big_endian" => Format::BinaryBigEndian,
+                    _ => return Err(PlyError::UnsupportedFormat(name.to_string())),
+                });
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| invalid("element count is not a number"))?,
+                properties: vec![],
+            }),
+            ["property", "list", count, item, name] => {
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| invalid("property declared before any element"))?;
+                element.properties.push(Property::List {
+                    name: name.to_string(),
+                    count: ScalarType::parse(count)
+                        .ok_or_else(|| invalid("unknown list count type"))?,
+                    item: ScalarType::parse(item)
+                        .ok_or_else(|| invalid("unknown list item type"))?,
+                });
+            }
+            ["property", value, name] => {
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| invalid("property declared before any element"))?;
+                element.properties.push(Property::Scalar {
+                    name: name.to_string(),
+                    value: ScalarType::parse(value)
+                        .ok_or_else(|| invalid("unknown property type"))?,
+                });
+            }
+            ["end_header"] => break,
+            _ => return Err(invalid(&format!("unexpected `{}`", line))),
+        }
+    }
+
+    let format = format.ok_or(PlyError::InvalidHeader {
+        line: line_number,
+        message: "missing `format` line".to_string(),
+    })?;
+
+    return Ok((format, elements, offset));
+}
+
+/// Reads one property value, or all items of a list property.
+fn read_property(
+    body: &mut Body,
+    property: &Property,
+    element: &str,
+) -> Result<Vec<f64>, PlyError> {
+    match property {
+        Property::Scalar { value, .. } => return Ok(vec![body.read(*value, element)?]),
+        Property::List { count, item, .. } => {
+            let count = body.read(*count, element)?;
+
+            if count < 0.0 {
+                return Err(PlyError::InvalidValue {
+                    element: element.to_string(),
+                    value: count.to_string(),
+                });
+            }
+
+            return (0..count as usize)
+                .map(|_| body.read(*item, element))
+                .collect();
+        }
+    }
+}
+
+fn read_vertices(body: &mut Body, element: &Element) -> Result<Vec<Vertex>, PlyError> {
+    let find = |names: &[&str]| {
+        element
+            .properties
+            .iter()
+            .position(|p| names.contains(&p.name()))
+    };
+    let required = |name: &'static str| find(&[name]).ok_or(PlyError::MissingVertexProperty(name));
+
+    let position = [required("x")?, required("y")?, required("z")?];
+    let normal = [find(&["nx"]), find(&["ny"]), find(&["nz"])];
+    let color = [
+        find(&["red", "r", "diffuse_red"]),
+        find(&["green", "g", "diffuse_green"]),
+        find(&["blue", "b", "diffuse_blue"]),
+    ];
+    let tex_coord = [
+        find(&["u", "s", "texture_u", "texture_s"]),
+        find(&["v", "t", "texture_v", "texture_t"]),
+    ];
+    // 8-bit color channels run to 255, floating point ones to 1.
+    let color_scale = match color[0].map(|i| &element.properties[i]) {
+        Some(Property::Scalar {
+            value: ScalarType::Float32 | ScalarType::Float64,
+            ..
+        }) => 1.0,
+        _ => 1.0 / 255.0,
+    };
+
+    // The count comes from the header, so it is not trusted for allocating.
+    let mut vertices = Vec::new();
+
+    for _ in 0..element.count {
+        let values: Vec<f32> = element
+            .properties
+            .iter()
+            .map(|property| {
+                let values = read_property(body, property, &element.name)?;
+
+                Ok(values.first().copied().unwrap_or(0.0) as f32)
+            })
+            .collect::<Result<_, PlyError>>()?;
+
+        let vector = |indices: [Option<usize>; 3]| match indices {
+            [Some(x), Some(y), Some(z)] => Some(Vector3::new(values[x], values[y], values[z])),
+            _ => None,
+        };
+
+        vertices.push(Vertex {
+            position: vector(position.map(Some)).unwrap(),
+            normal: vector(normal)
+                .filter(|n| Vector3::magnitude(n) > 0.0)
+                .map(|n| Vector3::normalize(&n)),
+            color: vector(color).map(|c| Vector3::scalar_multiplication(&c, color_scale)),
+            tex_coord: match tex_coord {
+                [Some(u), Some(v)] => Some((values[u], values[v])),
+                _ => None,
+            },
+        });
+    }
+
+    return Ok(vertices);
+}
+
+fn read_faces(body: &mut Body, element: &Element) -> Result<Vec<Vec<usize>>, PlyError> {
+    let indices = element
+        .properties
+        .iter()
+        .position(|p| p.name() == "vertex_indices" || p.name() == "vertex_index");
+    let mut faces = Vec::new();
+
+    for _ in 0..element.count {
+        let mut face = vec![];
+
+        for (i, property) in element.properties.iter().enumerate() {
+            let values = read_property(body, property, &element.name)?;
+
+            if Some(i) == indices {
+                // Float-typed lists are allowed, but only whole vertex numbers.
+                face = values
+                    .iter()
+                    .map(|&v| {
+                        if v < 0.0 || v.fract() != 0.0 {
+                            return Err(PlyError::InvalidValue {
+                                element: element.name.clone(),
+                                value: v.to_string(),
+                            });
+                        }
+
+                        Ok(v as usize)
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+        }
+
+        faces.push(face);
+    }
+
+    return Ok(faces);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::color_tools::Color;
+
+    static HEADER_BODY: &str = "element vertex 4\n\
+        property float x\n\
+        property float y\n\
+        property float z\n\
+        element face 1\n\
+        property list uchar int vertex_indices\n\
+        end_header\n";
+
+    static SQUARE: [[f32; 3]; 4] = [
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0],
+    ];
+
+    fn load(bytes: &[u8]) -> Result<Model3D, PlyError> {
+        return Model3D::new_from_ply_bytes(
+            Vector3::zero(),
+            bytes,
+            Material::diffuse(Color::White),
+        );
+    }
+
+    fn binary(format: &str, big_endian: bool) -> Vec<u8> {
+        let mut bytes = format!("ply\nformat {} 1.0\n{}", format, HEADER_BODY).into_bytes();
+
+        for vertex in SQUARE {
+            for component in vertex {
+                bytes.extend(if big_endian {
+                    component.to_be_bytes()
+                } else {
+                    component.to_le_bytes()
+                });
+            }
+        }
+
+        bytes.push(4);
+        for index in 0..4i32 {
+            bytes.extend(if big_endian {
+                index.to_be_bytes()
+            } else {
+                index.to_le_bytes()
+            });
+        }
+
+        return bytes;
+    }
+
+    fn assert_square(model: &Model3D) {
+        let vertex = |i: usize| Vector3::new(SQUARE[i][0], SQUARE[i][1], SQUARE[i][2]);
+
+        assert_eq!(model.triangles.len(), 2);
+        for triangle in &model.triangles {
+            assert!(triangle
+                .vertices
+                .iter()
+                .all(|v| (0..4).any(|i| *v == vertex(i))));
+            // Counter-clockwise seen from +z, like the face.
+            assert!(triangle.get_normal().z > 0.99);
+        }
+    }
+
+    #[test]
+    fn ascii() {
+        let text = format!(
+            "ply\nformat ascii 1.0\ncomment square\n{}0 0 0\n1 0 0\n1 1 0\n0 1 0\n4 0 1 2 3\n",
+            HEADER_BODY
+        );
+
+        assert_square(&load(text.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn binary_little_endian() {
+        assert_square(&load(&binary("binary_little_endian", false)).unwrap());
+    }
+
+    #[test]
+    fn binary_big_endian() {
+        assert_square(&load(&binary("binary_big_endian", true)).unwrap());
+    }
+
+    #[test]
+    fn face_index_out_of_range() {
+        let text = format!(
+            "ply\nformat ascii 1.0\n{}0 0 0\n1 0 0\n1 1 0\n0 1 0\n3 0 1 4\n",
+            HEADER_BODY
+        );
+
+        assert!(matches!(
+            load(text.as_bytes()),
+            Err(PlyError::IndexOutOfRange { face: 0, index: 4 })
+        ));
+    }
+
+    #[test]
+    fn malformed_face_index() {
+        let header = HEADER_BODY.replace("uchar int", "uchar float");
+
+        for face in ["3 0 1 -2", "3 0 1 1.5", "3 0 1 nan"] {
+            let text = format!(
+                "ply\nformat ascii 1.0\n{}0 0 0\n1 0 0\n1 1 0\n0 1 0\n{}\n",
+                header, face
+            );
+
+            assert!(
+                matches!(
+                    load(text.as_bytes()),
+                    Err(PlyError::InvalidValue { element, .. }) if element == "face"
+                ),
+                "{}",
+                face
+            );
+        }
+    }
+
+    #[test]
+    fn huge_element_count() {
+        let text = "ply\nformat ascii 1.0\nelement vertex 18446744073709551615\n\
+            property float x\nproperty float y\nproperty float z\nend_header\n0 0 0\n";
+
+        assert!(matches!(
+            load(text.as_bytes()),
+            Err(PlyError::UnexpectedEndOfData { element }) if element == "vertex"
+        ));
+    }
+
+    #[test]
+    fn not_ply() {
+        assert!(matches!(load(b"solid cube\n"), Err(PlyError::NotPly)));
+    }
+}
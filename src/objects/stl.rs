@@ -0,0 +1,299 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::sync::Arc;
+
+use super::{
+    material::Material,
+    model3D::{Model3D, DEFAULT_CREASE_ANGLE},
+    triangle::Triangle,
+};
+use crate::tools::vector3::Vector3;
+
+/// Size of the header preceding the triangle count in binary files.
+static BINARY_HEADER_SIZE: usize = 80;
+/// Normal, three vertices and the attribute byte count.
+static BINARY_TRIANGLE_SIZE: usize = 50;
+
+#[derive(Debug)]
+pub enum StlError {
+    Io(io::Error),
+    /// A binary file whose size does not match its triangle count.
+    Truncated {
+        expected: usize,
+        actual: usize,
+    },
+    InvalidNumber {
+        line: usize,
+        token: String,
+    },
+    UnexpectedToken {
+        line: usize,
+        token: String,
+    },
+    /// An ASCII facet with other than three vertices.
+    InvalidFacet {
+        line: usize,
+        vertices: usize,
+    },
+}
+
+impl fmt::Display for StlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StlError::Io(e) => write!(f, "cannot read STL file: {}", e),
+            StlError::Truncated { expected, actual } => write!(
+                f,
+                "binary STL file is {} bytes long, its triangle count needs {}",
+                actual, expected
+            ),
+            StlError::InvalidNumber { line, token } => {
+                write!(f, "invalid number `{}` in STL file at line {}", token, line)
+            }
+            StlError::UnexpectedToken { line, token } => {
+                write!(f, "unexpected `{}` in STL file at line {}", token, line)
+            }
+            StlError::InvalidFacet { line, vertices } => write!(
+                f,
+                "STL facet ending at line {} has {} vertices instead of 3",
+                line, vertices
+            ),
+        }
+    }
+}
+
+impl Error for StlError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StlError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for StlError {
+    fn from(e: io::Error) -> Self {
+        return StlError::Io(e);
+    }
+}
+
+impl Model3D {
+    /// Loads an ASCII or binary STL mesh. The stored facet normals are
+    /// ignored in favour of the vertex winding, which the format defines as
+    /// counter-clockwise seen from outside.
+    pub fn new_from_stl_file(
+        position: Vector3,
+        stl_path: &str,
+        material: Arc<Material>,
+    ) -> Result<Model3D, StlError> {
+        return Model3D::new_from_stl_bytes(position, &fs::read(stl_path)?, material);
+    }
+
+    /// Parses the contents of an STL file, see [`Model3D::new_from_stl_file`].
+    pub fn new_from_stl_bytes(
+        position: Vector3,
+        bytes: &[u8],
+        material: Arc<Material>,
+    ) -> Result<Model3D, StlError> {
+        let facets = if is_ascii(bytes) {
+            parse_ascii(&String::from_utf8_lossy(bytes))?
+        } else {
+            parse_binary(bytes)?
+        };
+
+        let mut triangles: Vec<Triangle> = facets
+            .into_iter()
+            .map(|[a, b, c]| Triangle::new(a, b, c, material.clone()))
+            .collect();
+
+        Model3D::compute_vertex_normals(&mut triangles, DEFAULT_CREASE_ANGLE);
+
+        return Ok(Model3D::new(position, triangles, material));
+    }
+}
+
+/// Binary files may also start with `solid`, so trust the size of the file
+/// when it matches its binary triangle count, and treat files containing NUL
+/// bytes as (possibly truncated) binary ones.
+fn is_ascii(bytes: &[u8]) -> bool {
+    if !bytes.trim_ascii_start().starts_with(b"solid") || bytes.contains(&0) {
+        return false;
+    }
+
+    return match binary_triangle_count(bytes) {
+        Some(count) => bytes.len() != BINARY_HEADER_SIZE + 4 + count * BINARY_TRIANGLE_SIZE,
+        None => true,
+    };
+}
+
+fn binary_triangle_count(bytes: &[u8]) -> Option<usize> {
+    let count = bytes.get(BINARY_HEADER_SIZE..BINARY_HEADER_SIZE + 4)?;
+
+    return Some(u32::from_le_bytes([count[0], count[1], count[2], count[3]]) as usize);
+}
+
+fn parse_binary(bytes: &[u8]) -> Result<Vec<[Vector3; 3]>, StlError> {
+    let count = binary_triangle_count(bytes).ok_or(StlError::Truncated {
+        expected: BINARY_HEADER_SIZE + 4,
+        actual: bytes.len(),
+    })?;
+    let expected = BINARY_HEADER_SIZE + 4 + count * BINARY_TRIANGLE_SIZE;
+
+    if bytes.len() < expected {
+        return Err(StlError::Truncated {
+            expected,
+            actual: bytes.len(),
+        });
+    }
+
+    let read_vector = |offset: usize| {
+        let component = |i: usize| {
+            let start = offset + i * 4;
+            f32::from_le_bytes([
+                bytes[start],
+                bytes[start + 1],
+                bytes[start + 2],
+                bytes[start + 3],
+            ])
+        };
+
+        Vector3::new(component(0), component(1), component(2))
+    };
+
+    return Ok((0..count)
+        .map(|t| {
+            // Skip the 12 byte facet normal.
+            let offset = BINARY_HEADER_SIZE + 4 + t * BINARY_TRIANGLE_SIZE + 12;
+
+            [
+                read_vector(offset),
+                read_vector(offset + 12),
+                read_vector(offset + 24),
+            ]
+        })
+        .collect());
+}
+
+fn parse_ascii(text: &str) -> Result<Vec<[Vector3; 3]>, StlError> {
+    let mut facets = vec![];
+    let mut vertices: Vec<Vector3> = vec![];
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let number = |token: &str| {
+            token.parse::<f32>().map_err(|_| StlError::InvalidNumber {
+                line: line_number,
+                token: token.to_string(),
+            })
+        };
+
+        match words.as_slice() {
+            [] | ["solid", ..] | ["endsolid", ..] | ["outer", "loop"] | ["endloop"] => {}
+            ["facet", "normal", _, _, _] => vertices.clear(),
+            ["vertex", x, y, z] => vertices.push(Vector3::new(number(x)?, number(y)?, number(z)?)),
+            ["endfacet"] => {
+                if vertices.len() != 3 {
+                    return Err(StlError::InvalidFacet {
+                        line: line_number,
+                        vertices: vertices.len(),
+                    });
+                }
+
+                facets.push([vertices[0], vertices[1], vertices[2]]);
+                vertices.clear();
+            }
+            [token, ..] => {
+                return Err(StlError::UnexpectedToken {
+                    line: line_number,
+                    token: token.to_string(),
+                })
+            }
+        }
+    }
+
+    return Ok(facets);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::color_tools::Color;
+
+    static TRIANGLE: [[f32; 3]; 3] = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+
+    fn load(bytes: &[u8]) -> Result<Model3D, StlError> {
+        return Model3D::new_from_stl_bytes(
+            Vector3::zero(),
+            bytes,
+            Material::diffuse(Color::White),
+        );
+    }
+
+    /// Binary file whose header starts with `solid`, as some exporters write.
+    fn binary() -> Vec<u8> {
+        let mut bytes = b"solid exported as binary".to_vec();
+        bytes.resize(BINARY_HEADER_SIZE, b' ');
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend([0.0f32, 0.0, 1.0].iter().flat_map(|c| c.to_le_bytes()));
+        bytes.extend(TRIANGLE.iter().flatten().flat_map(|c| c.to_le_bytes()));
+        bytes.extend([0u8, 0]);
+
+        return bytes;
+    }
+
+    fn assert_triangle(model: &Model3D) {
+        assert_eq!(model.triangles.len(), 1);
+        for (vertex, expected) in model.triangles[0].vertices.iter().zip(TRIANGLE) {
+            assert_eq!(*vertex, Vector3::new(expected[0], expected[1], expected[2]));
+        }
+    }
+
+    #[test]
+    fn ascii() {
+        let text = "solid triangle\n\
+            facet normal 0 0 1\n\
+            outer loop\n\
+            vertex 0 0 0\n\
+            vertex 1 0 0\n\
+            vertex 0 1 0\n\
+            endloop\n\
+            endfacet\n\
+            endsolid triangle\n";
+
+        assert_triangle(&load(text.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn binary_starting_with_solid() {
+        assert_triangle(&load(&binary()).unwrap());
+    }
+
+    #[test]
+    fn truncated_binary() {
+        let bytes = binary();
+
+        assert!(matches!(
+            load(&bytes[..bytes.len() - 10]),
+            Err(StlError::Truncated {
+                expected: 134,
+                actual: 124
+            })
+        ));
+    }
+
+    #[test]
+    fn ascii_facet_missing_vertex() {
+        let text = "solid\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\n\
+            endloop\nendfacet\nendsolid\n";
+
+        assert!(matches!(
+            load(text.as_bytes()),
+            Err(StlError::InvalidFacet {
+                line: 7,
+                vertices: 2
+            })
+        ));
+    }
+}
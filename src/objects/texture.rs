@@ -0,0 +1,62 @@
+use std::path::Path;
+
+use image::{ImageError, RgbImage};
+
+use crate::tools::vector3::Vector3;
+
+/// Image looked up by surface UV coordinates, repeating outside [0, 1].
+#[derive(Debug)]
+pub struct Texture {
+    image: RgbImage,
+}
+
+impl Texture {
+    pub fn new(image: RgbImage) -> Texture {
+        return Texture { image };
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Texture, ImageError> {
+        return Ok(Texture::new(image::open(path)?.to_rgb8()));
+    }
+
+    /// Bilinearly filtered color at `uv`, with `v` pointing up the image as in
+    /// OBJ and most other mesh formats.
+    pub fn get_color(&self, uv: (f32, f32)) -> Vector3 {
+        let (width, height) = self.image.dimensions();
+
+        if width == 0 || height == 0 {
+            return Vector3::zero();
+        }
+
+        let x = uv.0.rem_euclid(1.0) * width as f32 - 0.5;
+        let y = (1.0 - uv.1.rem_euclid(1.0)) * height as f32 - 0.5;
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let tx = x - x0;
+        let ty = y - y0;
+
+        let texel = |x: f32, y: f32| {
+            let pixel = self.image.get_pixel(
+                (x as i64).rem_euclid(width as i64) as u32,
+                (y as i64).rem_euclid(height as i64) as u32,
+            );
+
+            Vector3::new(
+                pixel[0] as f32 / 255.0,
+                pixel[1] as f32 / 255.0,
+                pixel[2] as f32 / 255.0,
+            )
+        };
+        let lerp = |a: Vector3, b: Vector3, t: f32| {
+            Vector3::add(
+                &Vector3::scalar_multiplication(&a, 1.0 - t),
+                &Vector3::scalar_multiplication(&b, t),
+            )
+        };
+
+        let top = lerp(texel(x0, y0), texel(x0 + 1.0, y0), tx);
+        let bottom = lerp(texel(x0, y0 + 1.0), texel(x0 + 1.0, y0 + 1.0), tx);
+
+        return lerp(top, bottom, ty);
+    }
+}
@@ -0,0 +1,66 @@
+use crate::objects::camera::Projection;
+use crate::tools::filter::Filter;
+
+/// How radiance is estimated for each camera ray.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Integrator {
+    /// Direct lighting from the scene lights plus recursive mirror reflection and
+    /// refraction. Deterministic along each camera ray; more samples per pixel
+    /// only smooth edges and depth of field.
+    Whitted,
+    /// Unidirectional Monte Carlo path tracing with global illumination, using
+    /// next-event estimation and multiple importance sampling for direct light.
+    PathTracer,
+}
+
+/// Per-pixel sample counts driven by noise: once a pixel has `min_samples`,
+/// sampling stops as soon as the estimated error of its brightness falls
+/// below `threshold`, or at `max_samples`.
+#[derive(Debug, Clone)]
+pub struct AdaptiveSampling {
+    /// Raised to 2, the fewest samples giving an error estimate.
+    pub min_samples: u32,
+    pub max_samples: u32,
+    /// Standard error of the pixel's mean brightness, relative to that
+    /// brightness, under which the pixel is considered converged.
+    pub threshold: f32,
+    /// Where to save an image of the samples taken per pixel, from blue at
+    /// `min_samples` to red at `max_samples`.
+    pub heatmap_path: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderSettings {
+    pub integrator: Integrator,
+    /// Camera rays traced per pixel, jittered over a grid of sub-pixel cells
+    /// (⌊√n⌋ on a side) and each through its own point of the lens. A single
+    /// sample goes through the pixel center.
+    pub samples_per_pixel: u32,
+    /// Adapts the number of samples to each pixel instead; the grid of
+    /// sub-pixel cells is then sized after `min_samples`.
+    pub adaptive_sampling: Option<AdaptiveSampling>,
+    /// How samples are weighed into the pixels around them.
+    pub filter: Filter,
+    /// Replaces the camera's projection when set, e.g. to render the same
+    /// view as a fisheye or a panorama.
+    pub projection: Option<Projection>,
+    /// Maximum number of bounces followed after the primary hit.
+    pub max_depth: u32,
+    /// Threads rendering tiles of the image in parallel; `None` uses one per
+    /// CPU core. The image comes out the same for any count.
+    pub threads: Option<usize>,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        return RenderSettings {
+            integrator: Integrator::Whitted,
+            samples_per_pixel: 1,
+            adaptive_sampling: None,
+            filter: Filter::Box { radius: 0.5 },
+            projection: None,
+            max_depth: 5,
+            threads: None,
+        };
+    }
+}
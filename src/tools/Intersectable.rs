@@ -1,20 +1,70 @@
 use crate::objects::{object3d::Object3D, ray::Ray};
 
+use super::{aabb::Aabb, vector3::Vector3};
+
 #[derive(Clone, Debug)]
 pub struct Intersection<'a> {
     pub distance: f32,
-    pub object: Object3D<'a>
+    /// World-space hit point.
+    pub point: Vector3,
+    /// Geometric normal, always facing against the incoming ray.
+    pub normal: Vector3,
+    /// Normal used for shading; equal to `normal` unless the primitive provides
+    /// an interpolated one. Oriented on the same side as `normal`.
+    pub shading_normal: Vector3,
+    pub uv: (f32, f32),
+    /// Whether the ray hit the side the outward normal points to.
+    pub front_face: bool,
+    /// Index of the triangle hit when the ray struck a mesh.
+    pub face_index: Option<usize>,
+    pub object: Object3D<'a>,
 }
 
 impl<'a> Intersection<'a> {
-    pub fn new(distance: f32, object: Object3D<'a>) -> Intersection<'a> {
+    pub fn new(
+        ray: &Ray,
+        distance: f32,
+        outward_normal: Vector3,
+        uv: (f32, f32),
+        object: Object3D<'a>,
+    ) -> Intersection<'a> {
+        let front_face = Vector3::dot_product(&ray.direction, &outward_normal) < 0.0;
+        let normal = if front_face {
+            outward_normal
+        } else {
+            -outward_normal
+        };
+
         return Intersection {
             distance,
-            object
-        }
+            point: ray.at(distance),
+            normal,
+            shading_normal: normal,
+            uv,
+            front_face,
+            face_index: None,
+            object,
+        };
+    }
+
+    /// Replaces the shading normal, flipping it onto the side of the geometric
+    /// normal if needed.
+    pub fn with_shading_normal(mut self, outward_shading_normal: Vector3) -> Intersection<'a> {
+        self.shading_normal = if self.front_face {
+            outward_shading_normal
+        } else {
+            -outward_shading_normal
+        };
+
+        return self;
     }
 }
 
 pub trait Intersectable {
-    fn get_intersection(&self, ray: &Ray) -> Option<Intersection>;   
+    /// Closest hit whose distance along `ray` lies strictly between `t_min`
+    /// and `t_max`.
+    fn get_intersection(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection>;
+
+    /// World-space bounds, or `None` for unbounded primitives such as planes.
+    fn get_bounds(&self) -> Option<Aabb>;
 }
@@ -1,6 +1,6 @@
 use num::NumCast;
 use std::fmt;
-use std::ops::{Add, Mul, Sub};
+use std::ops::{Add, Mul, Neg, Sub};
 
 #[derive(Clone, Debug, Copy, PartialEq)]
 pub struct Vector3 {
@@ -53,6 +53,23 @@ impl Vector3 {
         return Vector3::new(v.x * scalar, v.y * scalar, v.z * scalar);
     }
 
+    pub fn component_multiplication(a: &Vector3, b: &Vector3) -> Self {
+        return Vector3::new(a.x * b.x, a.y * b.y, a.z * b.z);
+    }
+
+    /// Component along `axis`: 0 = x, 1 = y, anything else = z.
+    pub fn get_component(v: &Vector3, axis: usize) -> f32 {
+        match axis {
+            0 => v.x,
+            1 => v.y,
+            _ => v.z,
+        }
+    }
+
+    pub fn max_component(v: &Vector3) -> f32 {
+        return v.x.max(v.y).max(v.z);
+    }
+
     pub fn magnitude(v: &Vector3) -> f32 {
         return Vector3::dot_product(&v, &v).sqrt();
     }
@@ -62,6 +79,47 @@ impl Vector3 {
 
         return Vector3::new(v.x / magnitude, v.y / magnitude, v.z / magnitude);
     }
+
+    /// Mirrors the direction `d` about the unit normal `n`.
+    pub fn reflect(d: &Vector3, n: &Vector3) -> Self {
+        return Vector3::sub(
+            d,
+            &Vector3::scalar_multiplication(n, 2.0 * Vector3::dot_product(d, n)),
+        );
+    }
+
+    /// Bends the unit direction `d` through a surface with unit normal `n` (facing
+    /// against `d`), where `eta` is the ratio of refractive indices n1 / n2.
+    /// Returns `None` on total internal reflection.
+    pub fn refract(d: &Vector3, n: &Vector3, eta: f32) -> Option<Self> {
+        let cos_i = -Vector3::dot_product(d, n);
+        let sin2_t = eta * eta * (1.0 - cos_i * cos_i);
+
+        if sin2_t > 1.0 {
+            return None;
+        }
+
+        let cos_t = (1.0 - sin2_t).sqrt();
+
+        return Some(Vector3::add(
+            &Vector3::scalar_multiplication(d, eta),
+            &Vector3::scalar_multiplication(n, eta * cos_i - cos_t),
+        ));
+    }
+
+    /// Builds two unit vectors that, together with the unit vector `n`, form an
+    /// orthonormal basis.
+    pub fn orthonormal_basis(n: &Vector3) -> (Vector3, Vector3) {
+        let helper = if n.x.abs() > 0.9 {
+            Vector3::new(0.0, 1.0, 0.0)
+        } else {
+            Vector3::new(1.0, 0.0, 0.0)
+        };
+        let tangent = Vector3::normalize(&Vector3::cross_product(&helper, n));
+        let bitangent = Vector3::cross_product(n, &tangent);
+
+        return (tangent, bitangent);
+    }
 }
 
 impl Add for Vector3 {
@@ -80,6 +138,14 @@ impl Sub for Vector3 {
     }
 }
 
+impl Neg for Vector3 {
+    type Output = Vector3;
+
+    fn neg(self) -> Self::Output {
+        return Vector3::new(-self.x, -self.y, -self.z);
+    }
+}
+
 impl<T: NumCast> Mul<T> for Vector3 {
     type Output = Vector3;
 
@@ -0,0 +1,195 @@
+use crate::objects::ray::Ray;
+
+use super::vector3::Vector3;
+
+/// Axis-aligned bounding box.
+#[derive(Clone, Debug, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vector3,
+    pub max: Vector3,
+}
+
+impl Aabb {
+    pub fn new(min: Vector3, max: Vector3) -> Self {
+        return Aabb { min, max };
+    }
+
+    /// Box containing nothing; the identity for [`Aabb::union`].
+    pub fn empty() -> Self {
+        return Aabb {
+            min: Vector3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: Vector3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        };
+    }
+
+    pub fn from_points(points: &[Vector3]) -> Self {
+        return points.iter().fold(Aabb::empty(), |bounds, point| {
+            Aabb::union_point(&bounds, point)
+        });
+    }
+
+    pub fn union(a: &Aabb, b: &Aabb) -> Self {
+        return Aabb {
+            min: Vector3::new(
+                a.min.x.min(b.min.x),
+                a.min.y.min(b.min.y),
+                a.min.z.min(b.min.z),
+            ),
+            max: Vector3::new(
+                a.max.x.max(b.max.x),
+                a.max.y.max(b.max.y),
+                a.max.z.max(b.max.z),
+            ),
+        };
+    }
+
+    pub fn union_point(a: &Aabb, p: &Vector3) -> Self {
+        return Aabb::union(a, &Aabb { min: *p, max: *p });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z;
+    }
+
+    pub fn get_centroid(&self) -> Vector3 {
+        return Vector3::scalar_multiplication(&Vector3::add(&self.min, &self.max), 0.5);
+    }
+
+    pub fn get_extent(&self) -> Vector3 {
+        return Vector3::sub(&self.max, &self.min);
+    }
+
+    pub fn get_surface_area(&self) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+
+        let e = self.get_extent();
+
+        return 2.0 * (e.x * e.y + e.y * e.z + e.z * e.x);
+    }
+
+    /// Index (0 = x, 1 = y, 2 = z) of the axis along which the box is widest.
+    pub fn get_longest_axis(&self) -> usize {
+        let e = self.get_extent();
+
+        if e.x >= e.y && e.x >= e.z {
+            return 0;
+        } else if e.y >= e.z {
+            return 1;
+        }
+
+        return 2;
+    }
+
+    /// Slab test. Returns the distance at which `ray` enters the box, clamped
+    /// to `t_min`, as long as the box overlaps the `t_min`..`t_max` range.
+    /// `inv_direction` is the component-wise reciprocal of the ray direction.
+    pub fn intersect(
+        &self,
+        ray: &Ray,
+        inv_direction: &Vector3,
+        t_min: f32,
+        t_max: f32,
+    ) -> Option<f32> {
+        let mut t_enter = t_min;
+        let mut t_exit = t_max;
+
+        for axis in 0..3 {
+            let origin = Vector3::get_component(&ray.origin, axis);
+            let inv = Vector3::get_component(inv_direction, axis);
+            let min = Vector3::get_component(&self.min, axis);
+            let max = Vector3::get_component(&self.max, axis);
+
+            // Parallel to the slab, the ray is inside it everywhere or nowhere.
+            // Testing this directly avoids the NaN of 0 * inf on its boundary.
+            if inv.is_infinite() {
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+
+            let t0 = (min - origin) * inv;
+            let t1 = (max - origin) * inv;
+            let (near, far) = if t0 <= t1 { (t0, t1) } else { (t1, t0) };
+
+            t_enter = t_enter.max(near);
+            t_exit = t_exit.min(far);
+
+            if t_enter > t_exit {
+                return None;
+            }
+        }
+
+        return Some(t_enter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box() -> Aabb {
+        return Aabb::new(Vector3::zero(), Vector3::new(1.0, 1.0, 1.0));
+    }
+
+    /// Ray with its direction taken as is, so signed zeros survive.
+    fn intersect(origin: Vector3, direction: Vector3) -> Option<f32> {
+        let ray = Ray { origin, direction };
+        let inv = Vector3::new(1.0 / direction.x, 1.0 / direction.y, 1.0 / direction.z);
+
+        return unit_box().intersect(&ray, &inv, 0.0, f32::INFINITY);
+    }
+
+    #[test]
+    fn slab_hit_and_miss() {
+        let direction = Vector3::normalize(&Vector3::new(1.0, 1.0, 1.0));
+
+        let t = intersect(Vector3::new(-1.0, -1.0, -1.0), direction).unwrap();
+        assert!((t - 3f32.sqrt()).abs() < 1e-5);
+        assert!(intersect(Vector3::new(-1.0, -1.0, -1.0), -direction).is_none());
+        assert!(intersect(Vector3::new(-1.0, 2.5, -1.0), direction).is_none());
+        // Starting inside, the range start is returned.
+        assert_eq!(intersect(Vector3::new(0.5, 0.5, 0.5), direction), Some(0.0));
+    }
+
+    #[test]
+    fn rays_parallel_to_slabs() {
+        for zero in [0.0, -0.0] {
+            let direction = Vector3::new(1.0, zero, zero);
+
+            assert_eq!(
+                intersect(Vector3::new(-1.0, 0.5, 0.5), direction),
+                Some(1.0)
+            );
+            // Outside the y slab, whatever the sign of the zero.
+            assert!(intersect(Vector3::new(-1.0, 2.0, 0.5), direction).is_none());
+            assert!(intersect(Vector3::new(-1.0, -1.0, 0.5), direction).is_none());
+            // On a slab boundary, 0 * inf gives NaN, which must not reject.
+            assert_eq!(
+                intersect(Vector3::new(-1.0, 1.0, 0.0), direction),
+                Some(1.0)
+            );
+        }
+    }
+
+    #[test]
+    fn range_limits() {
+        let ray = Ray::new(&Vector3::new(0.5, 0.5, -2.0), &Vector3::new(0.0, 0.0, 1.0));
+        let inv = Vector3::new(f32::INFINITY, f32::INFINITY, 1.0);
+
+        assert_eq!(unit_box().intersect(&ray, &inv, 0.0, 10.0), Some(2.0));
+        assert!(unit_box().intersect(&ray, &inv, 0.0, 1.5).is_none());
+        assert!(unit_box().intersect(&ray, &inv, 3.5, 10.0).is_none());
+    }
+
+    #[test]
+    fn empty_box() {
+        let empty = Aabb::empty();
+
+        assert!(empty.is_empty());
+        assert_eq!(empty.get_surface_area(), 0.0);
+        assert_eq!(Aabb::union(&empty, &unit_box()), unit_box());
+    }
+}
@@ -0,0 +1,478 @@
+use crate::objects::ray::Ray;
+
+use super::{aabb::Aabb, intersectable::Intersection, vector3::Vector3};
+
+/// Number of centroid buckets evaluated per split by the SAH builder.
+static SAH_BINS: usize = 12;
+/// Relative cost of visiting an interior node versus testing one primitive.
+static TRAVERSAL_COST: f32 = 1.0;
+static MAX_LEAF_SIZE: usize = 4;
+/// Deepest tree the builder produces; splits stop here regardless of the SAH.
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone)]
+enum BvhNode {
+    Leaf {
+        bounds: Aabb,
+        first: usize,
+        count: usize,
+    },
+    /// The left child always directly follows its parent in `nodes`.
+    Interior {
+        bounds: Aabb,
+        right: usize,
+        axis: usize,
+    },
+}
+
+/// Bounding volume hierarchy over a list of primitives, built with the surface
+/// area heuristic. It only stores primitive indices; callers test the actual
+/// primitives through the closures passed to the traversal methods.
+#[derive(Debug, Clone)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    indices: Vec<usize>,
+}
+
+impl Bvh {
+    /// Builds the hierarchy over primitives whose bounds are `bounds[i]`.
+    pub fn new(bounds: &[Aabb]) -> Self {
+        let mut bvh = Bvh {
+            nodes: Vec::new(),
+            indices: (0..bounds.len()).collect(),
+        };
+
+        if !bounds.is_empty() {
+            let centroids: Vec<Vector3> = bounds.iter().map(|b| b.get_centroid()).collect();
+            bvh.build(bounds, &centroids, 0, bounds.len(), 0);
+        }
+
+        return bvh;
+    }
+
+    pub fn get_bounds(&self) -> Aabb {
+        match self.nodes.first() {
+            Some(BvhNode::Leaf { bounds, .. }) | Some(BvhNode::Interior { bounds, .. }) => *bounds,
+            None => Aabb::empty(),
+        }
+    }
+
+    fn build(
+        &mut self,
+        bounds: &[Aabb],
+        centroids: &[Vector3],
+        start: usize,
+        end: usize,
+        depth: usize,
+    ) -> usize {
+        let node_bounds = self.indices[start..end]
+            .iter()
+            .fold(Aabb::empty(), |acc, &i| Aabb::union(&acc, &bounds[i]));
+        let node_index = self.nodes.len();
+        let count = end - start;
+
+        let leaf = BvhNode::Leaf {
+            bounds: node_bounds,
+            first: start,
+            count,
+        };
+
+        // Each level can leave one sibling on the traversal stack.
+        if count <= 1 || depth + 2 >= MAX_DEPTH {
+            self.nodes.push(leaf);
+            return node_index;
+        }
+
+        let centroid_bounds = Aabb::from_points(
+            &self.indices[start..end]
+                .iter()
+                .map(|&i| centroids[i])
+                .collect::<Vec<Vector3>>(),
+        );
+        let axis = centroid_bounds.get_longest_axis();
+        let axis_min = Vector3::get_component(&centroid_bounds.min, axis);
+        let axis_extent = Vector3::get_component(&centroid_bounds.get_extent(), axis);
+
+        // Every centroid in the same spot: no split can separate them.
+        if axis_extent <= 0.0 {
+            self.nodes.push(leaf);
+            return node_index;
+        }
+
+        let bin_of = |i: usize| -> usize {
+            let offset = (Vector3::get_component(&centroids[i], axis) - axis_min) / axis_extent;
+            ((offset * SAH_BINS as f32) as usize).min(SAH_BINS - 1)
+        };
+
+        let mut bin_bounds = vec![Aabb::empty(); SAH_BINS];
+        let mut bin_counts = vec![0usize; SAH_BINS];
+
+        for &i in &self.indices[start..end] {
+            let bin = bin_of(i);
+            bin_bounds[bin] = Aabb::union(&bin_bounds[bin], &bounds[i]);
+            bin_counts[bin] += 1;
+        }
+
+        // Cost of splitting after each bin, sweeping from both ends.
+        let mut best_cost = f32::INFINITY;
+        let mut best_split = 0;
+
+        for split in 0..SAH_BINS - 1 {
+            let (left, right) = bin_bounds.split_at(split + 1);
+            let (left_counts, right_counts) = bin_counts.split_at(split + 1);
+            let left_bounds = left.iter().fold(Aabb::empty(), |a, b| Aabb::union(&a, b));
+            let right_bounds = right.iter().fold(Aabb::empty(), |a, b| Aabb::union(&a, b));
+            let left_count: usize = left_counts.iter().sum();
+            let right_count: usize = right_counts.iter().sum();
+
+            if left_count == 0 || right_count == 0 {
+                continue;
+            }
+
+            let cost = TRAVERSAL_COST
+                + (left_bounds.get_surface_area() * left_count as f32
+                    + right_bounds.get_surface_area() * right_count as f32)
+                    / node_bounds.get_surface_area().max(f32::MIN_POSITIVE);
+
+            if cost < best_cost {
+                best_cost = cost;
+                best_split = split;
+            }
+        }
+
+        if best_cost == f32::INFINITY || (best_cost >= count as f32 && count <= MAX_LEAF_SIZE) {
+            self.nodes.push(leaf);
+            return node_index;
+        }
+
+        // Partition the index range in place around the chosen bin boundary.
+        let mut mid = start;
+        for i in start..end {
+            if bin_of(self.indices[i]) <= best_split {
+                self.indices.swap(i, mid);
+                mid += 1;
+            }
+        }
+
+        self.nodes.push(BvhNode::Interior {
+            bounds: node_bounds,
+            right: 0,
+            axis,
+        });
+        self.build(bounds, centroids, start, mid, depth + 1);
+        let right_index = self.build(bounds, centroids, mid, end, depth + 1);
+
+        if let BvhNode::Interior { right, .. } = &mut self.nodes[node_index] {
+            *right = right_index;
+        }
+
+        return node_index;
+    }
+
+    /// Closest hit along `ray` between `t_min` and `t_max`. `intersect` tests
+    /// the primitive with the given index for hits closer than the distance it
+    /// is passed, which shrinks to the closest hit so far; subtrees beyond that
+    /// distance are skipped.
+    pub fn get_intersection<'a, F>(
+        &self,
+        ray: &Ray,
+        t_min: f32,
+        t_max: f32,
+        mut intersect: F,
+    ) -> Option<Intersection<'a>>
+    where
+        F: FnMut(usize, f32) -> Option<Intersection<'a>>,
+    {
+        let mut closest: Option<Intersection<'a>> = None;
+
+        self.traverse(ray, t_min, t_max, |index, t_max| {
+            if let Some(inter) = intersect(index, t_max) {
+                if inter.distance < t_max {
+                    closest = Some(inter);
+                }
+            }
+
+            return closest.as_ref().map_or(t_max, |inter| inter.distance);
+        });
+
+        return closest;
+    }
+
+    /// Any-hit query: true as soon as `is_hit` reports a blocker for some
+    /// primitive whose bounds `ray` overlaps between `t_min` and `t_max`.
+    pub fn any_hit<F>(&self, ray: &Ray, t_min: f32, t_max: f32, mut is_hit: F) -> bool
+    where
+        F: FnMut(usize) -> bool,
+    {
+        let mut found = false;
+
+        self.traverse(ray, t_min, t_max, |index, _| {
+            if is_hit(index) {
+                found = true;
+                return t_min;
+            }
+
+            return t_max;
+        });
+
+        return found;
+    }
+
+    /// Front-to-back traversal of the nodes `ray` overlaps between `t_min` and
+    /// `t_max`. `visit` receives each primitive index in the leaves reached and
+    /// the current pruning distance, and returns the new one; returning `t_min`
+    /// or less stops the traversal.
+    fn traverse<F>(&self, ray: &Ray, t_min: f32, t_max: f32, mut visit: F)
+    where
+        F: FnMut(usize, f32) -> f32,
+    {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        let inv_direction = Vector3::new(
+            1.0 / ray.direction.x,
+            1.0 / ray.direction.y,
+            1.0 / ray.direction.z,
+        );
+        let mut t_max = t_max;
+        // Fixed-size stack: the SAH build keeps trees far shallower than this.
+        let mut stack = [0usize; MAX_DEPTH];
+        let mut stack_size = 1;
+
+        while stack_size > 0 {
+            stack_size -= 1;
+            let node_index = stack[stack_size];
+
+            match &self.nodes[node_index] {
+                BvhNode::Leaf {
+                    bounds,
+                    first,
+                    count,
+                } => {
+                    if bounds
+                        .intersect(ray, &inv_direction, t_min, t_max)
+                        .is_none()
+                    {
+                        continue;
+                    }
+
+                    for &index in &self.indices[*first..*first + *count] {
+                        t_max = visit(index, t_max);
+
+                        if t_max <= t_min {
+                            return;
+                        }
+                    }
+                }
+                BvhNode::Interior {
+                    bounds,
+                    right,
+                    axis,
+                } => {
+                    if bounds
+                        .intersect(ray, &inv_direction, t_min, t_max)
+                        .is_none()
+                    {
+                        continue;
+                    }
+
+                    // Visit the child on the side the ray comes from first.
+                    let (near, far) = if Vector3::get_component(&ray.direction, *axis) < 0.0 {
+                        (*right, node_index + 1)
+                    } else {
+                        (node_index + 1, *right)
+                    };
+
+                    stack[stack_size] = far;
+                    stack[stack_size + 1] = near;
+                    stack_size += 2;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::objects::{material::Material, triangle::Triangle};
+    use crate::tools::{color_tools::Color, intersectable::Intersectable};
+
+    fn random_point(rng: &mut SmallRng, extent: f32) -> Vector3 {
+        return Vector3::new(
+            rng.gen_range(-extent..extent),
+            rng.gen_range(-extent..extent),
+            rng.gen_range(-extent..extent),
+        );
+    }
+
+    /// Small triangles scattered through a cube of side 20.
+    fn triangle_soup(count: usize, rng: &mut SmallRng) -> Vec<Triangle> {
+        let material: Arc<Material> = Material::diffuse(Color::White);
+
+        return (0..count)
+            .map(|_| {
+                let center = random_point(rng, 10.0);
+                let [a, b, c] = [0; 3].map(|_| Vector3::add(&center, &random_point(rng, 1.0)));
+
+                Triangle::new(a, b, c, material.clone())
+            })
+            .collect();
+    }
+
+    fn build(triangles: &[Triangle]) -> Bvh {
+        let bounds: Vec<Aabb> = triangles.iter().map(|t| t.get_bounds().unwrap()).collect();
+
+        return Bvh::new(&bounds);
+    }
+
+    /// Closest hit as (distance, index), through the BVH and by testing every
+    /// triangle.
+    fn nearest(bvh: &Bvh, triangles: &[Triangle], ray: &Ray) -> [Option<(f32, usize)>; 2] {
+        let through_bvh = bvh
+            .get_intersection(ray, 0.0, f32::INFINITY, |index, t_max| {
+                let mut inter = triangles[index].get_intersection(ray, 0.0, t_max)?;
+                inter.face_index = Some(index);
+
+                Some(inter)
+            })
+            .map(|inter| (inter.distance, inter.face_index.unwrap()));
+
+        let mut brute_force = None;
+        for (index, triangle) in triangles.iter().enumerate() {
+            let t_max = brute_force.map_or(f32::INFINITY, |(t, _)| t);
+
+            if let Some(inter) = triangle.get_intersection(ray, 0.0, t_max) {
+                brute_force = Some((inter.distance, index));
+            }
+        }
+
+        return [through_bvh, brute_force];
+    }
+
+    fn assert_matches_brute_force(triangles: &[Triangle], rng: &mut SmallRng) {
+        let bvh = build(triangles);
+        let mut hits = 0;
+
+        for _ in 0..2000 {
+            // Rays from outside the soup towards a point inside it, plus some
+            // from within.
+            let origin = random_point(rng, 15.0);
+            let direction = Vector3::sub(&random_point(rng, 8.0), &origin);
+            let ray = Ray::new(&origin, &direction);
+
+            let [through_bvh, brute_force] = nearest(&bvh, triangles, &ray);
+            assert_eq!(through_bvh, brute_force);
+            assert_eq!(
+                bvh.any_hit(&ray, 0.0, f32::INFINITY, |index| {
+                    triangles[index]
+                        .get_intersection(&ray, 0.0, f32::INFINITY)
+                        .is_some()
+                }),
+                brute_force.is_some()
+            );
+            hits += brute_force.is_some() as usize;
+        }
+
+        assert!(hits > 100, "too few rays hit to test anything");
+    }
+
+    #[test]
+    fn random_soup_matches_brute_force() {
+        let mut rng = SmallRng::seed_from_u64(7);
+        let triangles = triangle_soup(500, &mut rng);
+
+        assert_matches_brute_force(&triangles, &mut rng);
+    }
+
+    #[test]
+    fn empty() {
+        let bvh = Bvh::new(&[]);
+        let ray = Ray::new(&Vector3::zero(), &Vector3::new(0.0, 0.0, 1.0));
+
+        assert!(bvh.get_bounds().is_empty());
+        assert!(bvh
+            .get_intersection(&ray, 0.0, f32::INFINITY, |_, _| panic!("no primitives"))
+            .is_none());
+        assert!(!bvh.any_hit(&ray, 0.0, f32::INFINITY, |_| panic!("no primitives")));
+    }
+
+    #[test]
+    fn single_primitive() {
+        let triangle = Triangle::new(
+            Vector3::new(-1.0, -1.0, 5.0),
+            Vector3::new(1.0, -1.0, 5.0),
+            Vector3::new(0.0, 1.0, 5.0),
+            Material::diffuse(Color::White),
+        );
+        let triangles = [triangle];
+        let bvh = build(&triangles);
+
+        let hit = Ray::new(&Vector3::zero(), &Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!(nearest(&bvh, &triangles, &hit)[0], Some((5.0, 0)));
+
+        let miss = Ray::new(&Vector3::zero(), &Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(nearest(&bvh, &triangles, &miss)[0], None);
+    }
+
+    #[test]
+    fn identical_centroids() {
+        // Nested triangles of growing size around the same centroid: no
+        // split axis separates them.
+        let material = Material::diffuse(Color::White);
+        let triangles: Vec<Triangle> = (1..=40)
+            .map(|i| {
+                let size = i as f32 * 0.25;
+                Triangle::new(
+                    Vector3::new(-size, -size, -0.1 * size),
+                    Vector3::new(size, -size, 0.1 * size),
+                    Vector3::new(0.0, size, 0.0),
+                    material.clone(),
+                )
+            })
+            .collect();
+        let centroid = triangles[0].get_bounds().unwrap().get_centroid();
+        let bvh = build(&triangles);
+
+        assert!(triangles.iter().all(|t| Vector3::magnitude(&Vector3::sub(
+            &t.get_bounds().unwrap().get_centroid(),
+            &centroid
+        )) < 1e-5));
+        assert_matches_brute_force(&triangles, &mut SmallRng::seed_from_u64(11));
+        assert_eq!(
+            bvh.get_bounds(),
+            Aabb::from_points(&[
+                Vector3::new(-10.0, -10.0, -1.0),
+                Vector3::new(10.0, 10.0, 1.0),
+            ])
+        );
+    }
+
+    #[test]
+    fn axis_parallel_rays() {
+        let mut rng = SmallRng::seed_from_u64(3);
+        let triangles = triangle_soup(300, &mut rng);
+        let bvh = build(&triangles);
+
+        for _ in 0..500 {
+            let origin = random_point(&mut rng, 12.0);
+
+            for direction in [
+                Vector3::new(1.0, 0.0, -0.0),
+                Vector3::new(-0.0, -1.0, 0.0),
+                Vector3::new(0.0, -0.0, 1.0),
+            ] {
+                // Built directly so the signed zeros are kept.
+                let ray = Ray { origin, direction };
+                let [through_bvh, brute_force] = nearest(&bvh, &triangles, &ray);
+
+                assert_eq!(through_bvh, brute_force);
+            }
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use image::Rgb;
 
+use super::vector3::Vector3;
+
 pub type ColorType = Rgb<u8>;
 #[allow(unused)]
 #[derive(Debug, Clone, Copy)]
@@ -47,6 +49,34 @@ impl Color {
         Rgb([r, g, b])
     }
 
+    /// Converts a linear color with channels in [0, 1] into a displayable pixel,
+    /// clamping anything outside that range.
+    pub fn from_vector3(color: &Vector3) -> ColorType {
+        let to_channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        Rgb([
+            to_channel(color.x),
+            to_channel(color.y),
+            to_channel(color.z),
+        ])
+    }
+
+    /// Perceived brightness of a linear color (Rec. 709 weights).
+    pub fn luminance(color: &Vector3) -> f32 {
+        return 0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z;
+    }
+
+    /// Returns the color as a vector with each channel in [0, 1].
+    pub fn to_vector3(self) -> Vector3 {
+        let rgb = self.rgb();
+
+        return Vector3::new(
+            rgb[0] as f32 / 255.0,
+            rgb[1] as f32 / 255.0,
+            rgb[2] as f32 / 255.0,
+        );
+    }
+
     pub fn rgb(&self) -> ColorType {
         match self {
             Color::Red => Rgb([255, 0, 0]),
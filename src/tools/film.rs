@@ -0,0 +1,198 @@
+use super::{filter::Filter, vector3::Vector3};
+
+/// Image, or rectangular region of one, being rendered. Camera samples are
+/// splatted onto every pixel within the filter's reach, and each pixel ends up
+/// as the filter-weighted average of the samples around it.
+pub struct Film {
+    /// Image coordinates of the region's top-left pixel.
+    pub origin_x: usize,
+    pub origin_y: usize,
+    pub width: usize,
+    pub height: usize,
+    filter: Filter,
+    weighted_sums: Vec<Vector3>,
+    weights: Vec<f32>,
+}
+
+impl Film {
+    pub fn new(width: usize, height: usize, filter: Filter) -> Self {
+        return Film::new_region(0, 0, width, height, filter);
+    }
+
+    /// Region of `width` × `height` pixels of an image, starting at pixel
+    /// (`origin_x`, `origin_y`). Samples only reach the pixels inside it.
+    pub fn new_region(
+        origin_x: usize,
+        origin_y: usize,
+        width: usize,
+        height: usize,
+        filter: Filter,
+    ) -> Self {
+        return Film {
+            origin_x,
+            origin_y,
+            width,
+            height,
+            filter,
+            weighted_sums: vec![Vector3::zero(); width * height],
+            weights: vec![0.0; width * height],
+        };
+    }
+
+    /// Adds the `radiance` seen through the image point (`x`, `y`), in pixels
+    /// from the top-left corner of the image.
+    pub fn add_sample(&mut self, x: f32, y: f32, radiance: &Vector3) {
+        let radius = self.filter.radius();
+        // Pixel centers lie at half-integer coordinates.
+        let first_x = (x - 0.5 - radius).ceil().max(self.origin_x as f32);
+        let last_x = (x - 0.5 + radius)
+            .floor()
+            .min((self.origin_x + self.width) as f32 - 1.0);
+        let first_y = (y - 0.5 - radius).ceil().max(self.origin_y as f32);
+        let last_y = (y - 0.5 + radius)
+            .floor()
+            .min((self.origin_y + self.height) as f32 - 1.0);
+
+        if last_x < first_x || last_y < first_y {
+            return;
+        }
+
+        for pixel_y in first_y as usize..=last_y as usize {
+            for pixel_x in first_x as usize..=last_x as usize {
+                let weight = self
+                    .filter
+                    .evaluate(pixel_x as f32 + 0.5 - x, pixel_y as f32 + 0.5 - y);
+
+                if weight == 0.0 {
+                    continue;
+                }
+
+                let index = self.get_index(pixel_x, pixel_y);
+                self.weighted_sums[index] = Vector3::add(
+                    &self.weighted_sums[index],
+                    &Vector3::scalar_multiplication(radiance, weight),
+                );
+                self.weights[index] += weight;
+            }
+        }
+    }
+
+    /// Adds the samples splatted onto `other` to the pixels both films cover.
+    pub fn merge(&mut self, other: &Film) {
+        let first_x = self.origin_x.max(other.origin_x);
+        let last_x = (self.origin_x + self.width).min(other.origin_x + other.width);
+        let first_y = self.origin_y.max(other.origin_y);
+        let last_y = (self.origin_y + self.height).min(other.origin_y + other.height);
+
+        for y in first_y..last_y {
+            for x in first_x..last_x {
+                let index = self.get_index(x, y);
+                let other_index = other.get_index(x, y);
+
+                self.weighted_sums[index] = Vector3::add(
+                    &self.weighted_sums[index],
+                    &other.weighted_sums[other_index],
+                );
+                self.weights[index] += other.weights[other_index];
+            }
+        }
+    }
+
+    /// Reconstructed radiance of the pixel at image coordinates (`x`, `y`);
+    /// black when no sample reached it, or when negative filter lobes leave
+    /// it with no positive weight to normalize by.
+    pub fn get_pixel(&self, x: usize, y: usize) -> Vector3 {
+        let index = self.get_index(x, y);
+
+        if self.weights[index] < 1e-8 {
+            return Vector3::zero();
+        }
+
+        return Vector3::scalar_multiplication(
+            &self.weighted_sums[index],
+            1.0 / self.weights[index],
+        );
+    }
+
+    fn get_index(&self, x: usize, y: usize) -> usize {
+        return (y - self.origin_y) * self.width + (x - self.origin_x);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::tools::test_utils::assert_near;
+
+    static MITCHELL: Filter = Filter::Mitchell {
+        radius: 2.0,
+        b: 1.0 / 3.0,
+        c: 1.0 / 3.0,
+    };
+
+    #[test]
+    fn unreached_pixel_is_black() {
+        let mut film = Film::new(4, 1, Filter::Box { radius: 0.5 });
+        film.add_sample(0.5, 0.5, &Vector3::new(1.0, 1.0, 1.0));
+
+        assert_near(&film.get_pixel(0, 0), &Vector3::new(1.0, 1.0, 1.0));
+        assert_eq!(film.get_pixel(3, 0), Vector3::zero());
+    }
+
+    #[test]
+    fn non_positive_weight_is_black() {
+        // Pixel 0 only sees a sample 1.5 pixels away, in Mitchell's negative
+        // lobe.
+        let mut film = Film::new_region(0, 0, 1, 1, MITCHELL);
+        film.add_sample(2.0, 0.5, &Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(film.get_pixel(0, 0), Vector3::zero());
+
+        // A sample exactly at the tent's radius adds a zero weight.
+        let mut film = Film::new(1, 1, Filter::Tent { radius: 1.0 });
+        film.add_sample(1.5, 0.5, &Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(film.get_pixel(0, 0), Vector3::zero());
+    }
+
+    #[test]
+    fn merged_regions_match_single_film() {
+        let mut rng = SmallRng::seed_from_u64(5);
+        let samples: Vec<(f32, f32, Vector3)> = (0..500)
+            .map(|_| {
+                (
+                    rng.gen_range(0.0..8.0),
+                    rng.gen_range(0.0..6.0),
+                    Vector3::new(rng.gen::<f32>(), rng.gen::<f32>(), rng.gen::<f32>()),
+                )
+            })
+            .collect();
+
+        let mut single = Film::new(8, 6, MITCHELL);
+        for (x, y, radiance) in &samples {
+            single.add_sample(*x, *y, radiance);
+        }
+
+        // Two halves, each covering its pixels plus the filter's reach, like
+        // the renderer's tiles.
+        let mut left = Film::new_region(0, 0, 6, 6, MITCHELL);
+        let mut right = Film::new_region(2, 0, 6, 6, MITCHELL);
+        for (x, y, radiance) in &samples {
+            if *x < 4.0 {
+                left.add_sample(*x, *y, radiance);
+            } else {
+                right.add_sample(*x, *y, radiance);
+            }
+        }
+
+        let mut merged = Film::new(8, 6, MITCHELL);
+        merged.merge(&left);
+        merged.merge(&right);
+
+        for y in 0..6 {
+            for x in 0..8 {
+                assert_near(&merged.get_pixel(x, y), &single.get_pixel(x, y));
+            }
+        }
+    }
+}
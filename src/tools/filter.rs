@@ -0,0 +1,136 @@
+/// Pixel reconstruction filter: weighs each camera sample by its offset, in
+/// pixels, from the center of every pixel it reaches. All filters are
+/// separable and reach `radius` pixels along each axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    /// Equal weights; a radius of 0.5 averages the samples of each pixel alone.
+    Box { radius: f32 },
+    /// Weights falling linearly to zero at the radius.
+    Tent { radius: f32 },
+    /// Gaussian of falloff `alpha`, shifted down to reach zero at the radius.
+    Gaussian { radius: f32, alpha: f32 },
+    /// Mitchell-Netravali cubic, with b = c = 1/3 as the recommended
+    /// parameters. Its negative lobes sharpen edges.
+    Mitchell { radius: f32, b: f32, c: f32 },
+}
+
+impl Filter {
+    pub fn radius(&self) -> f32 {
+        return match *self {
+            Filter::Box { radius }
+            | Filter::Tent { radius }
+            | Filter::Gaussian { radius, .. }
+            | Filter::Mitchell { radius, .. } => radius,
+        };
+    }
+
+    /// Weight of a sample `dx`, `dy` pixels away from a pixel center.
+    pub fn evaluate(&self, dx: f32, dy: f32) -> f32 {
+        return self.evaluate_1d(dx) * self.evaluate_1d(dy);
+    }
+
+    fn evaluate_1d(&self, d: f32) -> f32 {
+        let d = d.abs();
+
+        return match *self {
+            // Inclusive, so samples rounded onto a pixel edge still count.
+            Filter::Box { radius } => {
+                if d <= radius {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Filter::Tent { radius } => (radius - d).max(0.0),
+            Filter::Gaussian { radius, alpha } => {
+                ((-alpha * d * d).exp() - (-alpha * radius * radius).exp()).max(0.0)
+            }
+            Filter::Mitchell { radius, b, c } => Filter::mitchell_1d(2.0 * d / radius, b, c),
+        };
+    }
+
+    /// The Mitchell-Netravali cubic, which spans [-2, 2].
+    fn mitchell_1d(x: f32, b: f32, c: f32) -> f32 {
+        if x >= 2.0 {
+            return 0.0;
+        }
+
+        if x >= 1.0 {
+            return ((-b - 6.0 * c) * x.powi(3)
+                + (6.0 * b + 30.0 * c) * x.powi(2)
+                + (-12.0 * b - 48.0 * c) * x
+                + (8.0 * b + 24.0 * c))
+                / 6.0;
+        }
+
+        return ((12.0 - 9.0 * b - 6.0 * c) * x.powi(3)
+            + (-18.0 + 12.0 * b + 6.0 * c) * x.powi(2)
+            + (6.0 - 2.0 * b))
+            / 6.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-5, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn box_filter() {
+        let filter = Filter::Box { radius: 0.5 };
+
+        assert_eq!(filter.evaluate(0.0, 0.0), 1.0);
+        assert_eq!(filter.evaluate(0.5, -0.5), 1.0);
+        assert_eq!(filter.evaluate(0.51, 0.0), 0.0);
+    }
+
+    #[test]
+    fn tent_filter() {
+        let filter = Filter::Tent { radius: 2.0 };
+
+        assert_near(filter.evaluate(0.0, 0.0), 4.0);
+        assert_near(filter.evaluate(1.0, 0.0), 2.0);
+        assert_eq!(filter.evaluate(0.0, -2.0), 0.0);
+        assert_eq!(filter.evaluate(3.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn gaussian_filter() {
+        let filter = Filter::Gaussian {
+            radius: 1.5,
+            alpha: 2.0,
+        };
+        let at_center = 1.0 - (-2.0f32 * 1.5 * 1.5).exp();
+
+        assert_near(filter.evaluate(0.0, 0.0), at_center * at_center);
+        assert_eq!(filter.evaluate(1.5, 0.0), 0.0);
+        assert_eq!(filter.evaluate(0.0, 2.0), 0.0);
+        assert!(filter.evaluate(0.5, 0.0) < filter.evaluate(0.25, 0.0));
+    }
+
+    #[test]
+    fn mitchell_filter() {
+        let filter = Filter::Mitchell {
+            radius: 2.0,
+            b: 1.0 / 3.0,
+            c: 1.0 / 3.0,
+        };
+        let at_center = 16.0 / 18.0;
+
+        assert_near(filter.evaluate(0.0, 0.0), at_center * at_center);
+        assert_eq!(filter.evaluate(2.0, 0.0), 0.0);
+        // Negative lobe between one and two pixels out.
+        assert_near(filter.evaluate(1.5, 0.0), -5.0 / 144.0 * at_center);
+        assert!(filter.evaluate(1.5, 1.5) > 0.0);
+        // The two pieces of the cubic meet at one pixel, where the inner one
+        // is 1/18 for these parameters.
+        assert_near(filter.evaluate(1.0, 0.0), at_center / 18.0);
+        assert_near(
+            Filter::mitchell_1d(1.0 - 1e-6, 1.0 / 3.0, 1.0 / 3.0),
+            1.0 / 18.0,
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use std::fmt;
+
+use super::vector3::Vector3;
+
+/// Row-major 4x4 matrix acting on column vectors, so `a * b` applies `b`
+/// first. Angles are in degrees, like the camera's field of view.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix4 {
+    pub m: [[f32; 4]; 4],
+}
+
+impl Matrix4 {
+    pub fn new(m: [[f32; 4]; 4]) -> Matrix4 {
+        return Matrix4 { m };
+    }
+
+    pub fn identity() -> Matrix4 {
+        return Matrix4::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+    }
+
+    /// Builds a matrix from its columns, the layout used by glTF and OpenGL.
+    pub fn from_columns(columns: [[f32; 4]; 4]) -> Matrix4 {
+        return Matrix4::transpose(&Matrix4::new(columns));
+    }
+
+    pub fn translation(offset: &Vector3) -> Matrix4 {
+        return Matrix4::new([
+            [1.0, 0.0, 0.0, offset.x],
+            [0.0, 1.0, 0.0, offset.y],
+            [0.0, 0.0, 1.0, offset.z],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+    }
+
+    pub fn scaling(factors: &Vector3) -> Matrix4 {
+        return Matrix4::new([
+            [factors.x, 0.0, 0.0, 0.0],
+            [0.0, factors.y, 0.0, 0.0],
+            [0.0, 0.0, factors.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+    }
+
+    pub fn rotation_x(degrees: f32) -> Matrix4 {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+
+        return Matrix4::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, cos, -sin, 0.0],
+            [0.0, sin, cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+    }
+
+    pub fn rotation_y(degrees: f32) -> Matrix4 {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+
+        return Matrix4::new([
+            [cos, 0.0, sin, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [-sin, 0.0, cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+    }
+
+    pub fn rotation_z(degrees: f32) -> Matrix4 {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+
+        return Matrix4::new([
+            [cos, -sin, 0.0, 0.0],
+            [sin, cos, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+    }
+
+    /// Rotation about x, then y, then z, by the components of `degrees`.
+    pub fn rotation_euler(degrees: &Vector3) -> Matrix4 {
+        return Matrix4::multiply(
+            &Matrix4::rotation_z(degrees.z),
+            &Matrix4::multiply(
+                &Matrix4::rotation_y(degrees.y),
+                &Matrix4::rotation_x(degrees.x),
+            ),
+        );
+    }
+
+    /// Counter-clockwise rotation by `degrees` about `axis` (Rodrigues' formula).
+    pub fn rotation_axis_angle(axis: &Vector3, degrees: f32) -> Matrix4 {
+        let a = Vector3::normalize(axis);
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let t = 1.0 - cos;
+
+        return Matrix4::new([
+            [
+                t * a.x * a.x + cos,
+                t * a.x * a.y - sin * a.z,
+                t * a.x * a.z + sin * a.y,
+                0.0,
+            ],
+            [
+                t * a.x * a.y + sin * a.z,
+                t * a.y * a.y + cos,
+                t * a.y * a.z - sin * a.x,
+                0.0,
+            ],
+            [
+                t * a.x * a.z - sin * a.y,
+                t * a.y * a.z + sin * a.x,
+                t * a.z * a.z + cos,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+    }
+
+    /// The transform applying `b`, then `a`.
+    pub fn multiply(a: &Matrix4, b: &Matrix4) -> Matrix4 {
+        let mut m = [[0.0; 4]; 4];
+
+        for (row, values) in m.iter_mut().enumerate() {
+            for (column, value) in values.iter_mut().enumerate() {
+                *value = (0..4).map(|k| a.m[row][k] * b.m[k][column]).sum();
+            }
+        }
+
+        return Matrix4::new(m);
+    }
+
+    pub fn transpose(matrix: &Matrix4) -> Matrix4 {
+        let mut m = [[0.0; 4]; 4];
+
+        for (row, values) in m.iter_mut().enumerate() {
+            for (column, value) in values.iter_mut().enumerate() {
+                *value = matrix.m[column][row];
+            }
+        }
+
+        return Matrix4::new(m);
+    }
+
+    /// Gauss-Jordan elimination with partial pivoting; `None` for singular
+    /// matrices.
+    pub fn inverse(matrix: &Matrix4) -> Option<Matrix4> {
+        let mut a = matrix.m;
+        let mut inverse = Matrix4::identity().m;
+
+        for column in 0..4 {
+            let pivot = (column..4)
+                .max_by(|&i, &j| a[i][column].abs().total_cmp(&a[j][column].abs()))
+                .unwrap_or(column);
+
+            if a[pivot][column].abs() < 1e-12 {
+                return None;
+            }
+
+            a.swap(column, pivot);
+            inverse.swap(column, pivot);
+
+            let scale = 1.0 / a[column][column];
+            for k in 0..4 {
+                a[column][k] *= scale;
+                inverse[column][k] *= scale;
+            }
+
+            for row in 0..4 {
+                if row == column {
+                    continue;
+                }
+
+                let factor = a[row][column];
+                for k in 0..4 {
+                    a[row][k] -= factor * a[column][k];
+                    inverse[row][k] -= factor * inverse[column][k];
+                }
+            }
+        }
+
+        return Some(Matrix4::new(inverse));
+    }
+
+    /// Determinant of the upper 3x3 block; negative for mirroring transforms.
+    pub fn determinant3(matrix: &Matrix4) -> f32 {
+        let m = &matrix.m;
+
+        return m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+    }
+
+    pub fn transform_point(matrix: &Matrix4, p: &Vector3) -> Vector3 {
+        let m = &matrix.m;
+        let w = m[3][0] * p.x + m[3][1] * p.y + m[3][2] * p.z + m[3][3];
+        let point = Vector3::new(
+            m[0][0] * p.x + m[0][1] * p.y + m[0][2] * p.z + m[0][3],
+            m[1][0] * p.x + m[1][1] * p.y + m[1][2] * p.z + m[1][3],
+            m[2][0] * p.x + m[2][1] * p.y + m[2][2] * p.z + m[2][3],
+        );
+
+        if w == 1.0 || w == 0.0 {
+            return point;
+        }
+
+        return Vector3::scalar_multiplication(&point, 1.0 / w);
+    }
+
+    /// Applies the linear part only, as for directions and offsets.
+    pub fn transform_vector(matrix: &Matrix4, v: &Vector3) -> Vector3 {
+        let m = &matrix.m;
+
+        return Vector3::new(
+            m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+        );
+    }
+}
+
+impl fmt::Display for Matrix4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Matrix4: [")?;
+        for row in &self.m {
+            writeln!(f, "  [{}, {}, {}, {}],", row[0], row[1], row[2], row[3])?;
+        }
+        write!(f, "]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: &Matrix4, b: &Matrix4) {
+        for row in 0..4 {
+            for column in 0..4 {
+                assert!(
+                    (a.m[row][column] - b.m[row][column]).abs() < 1e-5,
+                    "{} differs from {}",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn inverse_undoes_matrix() {
+        let matrix = Matrix4::multiply(
+            &Matrix4::translation(&Vector3::new(1.0, -2.0, 3.0)),
+            &Matrix4::multiply(
+                &Matrix4::rotation_axis_angle(&Vector3::new(1.0, 1.0, 0.0), 30.0),
+                &Matrix4::scaling(&Vector3::new(2.0, 0.5, 3.0)),
+            ),
+        );
+        let inverse = Matrix4::inverse(&matrix).unwrap();
+
+        assert_near(&Matrix4::multiply(&matrix, &inverse), &Matrix4::identity());
+        assert_near(&Matrix4::multiply(&inverse, &matrix), &Matrix4::identity());
+    }
+
+    #[test]
+    fn inverse_needs_pivoting() {
+        // Zero on the diagonal: a swap of rows is required.
+        let matrix = Matrix4::new([
+            [0.0, 1.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 2.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+
+        assert_near(
+            &Matrix4::multiply(&matrix, &Matrix4::inverse(&matrix).unwrap()),
+            &Matrix4::identity(),
+        );
+    }
+
+    #[test]
+    fn singular_matrix_has_no_inverse() {
+        let flattened = Matrix4::scaling(&Vector3::new(1.0, 0.0, 1.0));
+        let repeated_row = Matrix4::new([
+            [1.0, 2.0, 3.0, 0.0],
+            [2.0, 4.0, 6.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+
+        assert!(Matrix4::inverse(&flattened).is_none());
+        assert!(Matrix4::inverse(&repeated_row).is_none());
+    }
+
+    #[test]
+    fn rotation_axis_angle_matches_euler() {
+        assert_near(
+            &Matrix4::rotation_axis_angle(&Vector3::new(0.0, 0.0, 2.0), 90.0),
+            &Matrix4::rotation_z(90.0),
+        );
+        assert_near(
+            &Matrix4::rotation_axis_angle(&Vector3::new(1.0, 0.0, 0.0), -45.0),
+            &Matrix4::rotation_x(-45.0),
+        );
+    }
+}
@@ -1,3 +1,13 @@
+pub mod aabb;
+pub mod bvh;
 pub mod color_tools;
+pub mod film;
+pub mod filter;
 pub mod intersectable;
+pub mod matrix4;
+pub mod running_stats;
+pub mod sampling;
+#[cfg(test)]
+pub mod test_utils;
+pub mod transform;
 pub mod vector3;
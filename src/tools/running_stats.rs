@@ -0,0 +1,98 @@
+/// Running mean and variance of a stream of values, updated one value at a
+/// time with Welford's algorithm.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunningStats {
+    count: u32,
+    mean: f32,
+    /// Sum of squared differences from the current mean.
+    m2: f32,
+}
+
+impl RunningStats {
+    pub fn new() -> Self {
+        return RunningStats::default();
+    }
+
+    pub fn add(&mut self, value: f32) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f32;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    pub fn get_count(&self) -> u32 {
+        return self.count;
+    }
+
+    pub fn get_mean(&self) -> f32 {
+        return self.mean;
+    }
+
+    /// Unbiased sample variance; 0 until two values were added.
+    pub fn variance(&self) -> f32 {
+        if self.count < 2 {
+            return 0.0;
+        }
+
+        return self.m2 / (self.count - 1) as f32;
+    }
+
+    /// Estimated standard deviation of the mean itself.
+    pub fn standard_error(&self) -> f32 {
+        if self.count == 0 {
+            return 0.0;
+        }
+
+        return (self.variance() / self.count as f32).sqrt();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn matches_two_pass_reference() {
+        let mut rng = SmallRng::seed_from_u64(3);
+        // Offset far from zero, where a naive sum of squares loses precision.
+        let values: Vec<f32> = (0..1000).map(|_| 100.0 + rng.gen::<f32>()).collect();
+
+        let mut stats = RunningStats::new();
+        for &value in &values {
+            stats.add(value);
+        }
+
+        let n = values.len() as f64;
+        let mean = values.iter().map(|&v| v as f64).sum::<f64>() / n;
+        let variance = values
+            .iter()
+            .map(|&v| (v as f64 - mean).powi(2))
+            .sum::<f64>()
+            / (n - 1.0);
+
+        assert_eq!(stats.get_count(), 1000);
+        assert!((stats.get_mean() as f64 - mean).abs() < 1e-4);
+        assert!((stats.variance() as f64 - variance).abs() < 1e-3 * variance);
+        assert!(
+            (stats.standard_error() as f64 - (variance / n).sqrt()).abs()
+                < 1e-3 * (variance / n).sqrt()
+        );
+    }
+
+    #[test]
+    fn few_values() {
+        let mut stats = RunningStats::new();
+        assert_eq!(stats.variance(), 0.0);
+        assert_eq!(stats.standard_error(), 0.0);
+
+        stats.add(2.0);
+        assert_eq!(stats.get_mean(), 2.0);
+        assert_eq!(stats.variance(), 0.0);
+
+        stats.add(4.0);
+        assert_eq!(stats.get_mean(), 3.0);
+        assert_eq!(stats.variance(), 2.0);
+    }
+}
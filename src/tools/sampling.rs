@@ -0,0 +1,120 @@
+use std::f32::consts::PI;
+
+use rand::Rng;
+
+use super::vector3::Vector3;
+
+/// Direction in the hemisphere around the unit `normal`, distributed
+/// proportionally to the cosine with the normal (pdf = cos θ / π).
+pub fn cosine_hemisphere(normal: &Vector3, rng: &mut impl Rng) -> Vector3 {
+    let r1: f32 = rng.gen();
+    let r2: f32 = rng.gen();
+    let phi = 2.0 * PI * r1;
+    let r = r2.sqrt();
+
+    let (tangent, bitangent) = Vector3::orthonormal_basis(normal);
+
+    return Vector3::normalize(&Vector3::add(
+        &Vector3::add(
+            &Vector3::scalar_multiplication(&tangent, r * phi.cos()),
+            &Vector3::scalar_multiplication(&bitangent, r * phi.sin()),
+        ),
+        &Vector3::scalar_multiplication(normal, (1.0 - r2).max(0.0).sqrt()),
+    ));
+}
+
+/// Jittered point of the unit square for the `index`-th sample spread over a
+/// `strata` × `strata` grid: uniform inside the cell the index falls on, going
+/// around the grid again past its last cell.
+pub fn stratified_square(index: u32, strata: u32, rng: &mut impl Rng) -> (f32, f32) {
+    let cell = index % (strata * strata);
+    let (jitter_x, jitter_y): (f32, f32) = rng.gen();
+
+    return (
+        ((cell % strata) as f32 + jitter_x) / strata as f32,
+        ((cell / strata) as f32 + jitter_y) / strata as f32,
+    );
+}
+
+/// Maps a point of the unit square onto the unit disk (Shirley-Chiu concentric
+/// mapping). Uniform and stratified inputs stay so on the disk.
+pub fn concentric_disk(u: f32, v: f32) -> (f32, f32) {
+    let a = 2.0 * u - 1.0;
+    let b = 2.0 * v - 1.0;
+
+    if a == 0.0 && b == 0.0 {
+        return (0.0, 0.0);
+    }
+
+    let (r, phi) = if a.abs() > b.abs() {
+        (a, PI / 4.0 * (b / a))
+    } else {
+        (b, PI / 2.0 - PI / 4.0 * (a / b))
+    };
+
+    return (r * phi.cos(), r * phi.sin());
+}
+
+/// Direction around the unit `axis` distributed proportionally to cos^n α,
+/// α being the angle with the axis: the lobe of a Phong reflection of
+/// `exponent` n. Directions can fall up to 90° from the axis.
+pub fn phong_lobe(axis: &Vector3, exponent: f32, rng: &mut impl Rng) -> Vector3 {
+    let cos_alpha = rng.gen::<f32>().powf(1.0 / (exponent + 1.0));
+    let sin_alpha = (1.0 - cos_alpha * cos_alpha).max(0.0).sqrt();
+    let phi = 2.0 * PI * rng.gen::<f32>();
+
+    let (tangent, bitangent) = Vector3::orthonormal_basis(axis);
+
+    return Vector3::normalize(&Vector3::add(
+        &Vector3::add(
+            &Vector3::scalar_multiplication(&tangent, sin_alpha * phi.cos()),
+            &Vector3::scalar_multiplication(&bitangent, sin_alpha * phi.sin()),
+        ),
+        &Vector3::scalar_multiplication(axis, cos_alpha),
+    ));
+}
+
+/// Solid angle pdf of [`phong_lobe`] for a direction at `cos_alpha` from the axis.
+pub fn phong_lobe_pdf(cos_alpha: f32, exponent: f32) -> f32 {
+    if cos_alpha <= 0.0 {
+        return 0.0;
+    }
+
+    return (exponent + 1.0) / (2.0 * PI) * cos_alpha.powf(exponent);
+}
+
+/// Direction inside the cone around the unit `axis` with half-angle θmax, uniform
+/// over the subtended solid angle. The cone is given as 1 - cos θmax, which
+/// stays precise for narrow cones.
+pub fn uniform_cone(axis: &Vector3, one_minus_cos_max: f32, rng: &mut impl Rng) -> Vector3 {
+    let cos_theta = 1.0 - rng.gen::<f32>() * one_minus_cos_max;
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let phi = 2.0 * PI * rng.gen::<f32>();
+
+    let (tangent, bitangent) = Vector3::orthonormal_basis(axis);
+
+    return Vector3::normalize(&Vector3::add(
+        &Vector3::add(
+            &Vector3::scalar_multiplication(&tangent, sin_theta * phi.cos()),
+            &Vector3::scalar_multiplication(&bitangent, sin_theta * phi.sin()),
+        ),
+        &Vector3::scalar_multiplication(axis, cos_theta),
+    ));
+}
+
+pub fn uniform_cone_pdf(one_minus_cos_max: f32) -> f32 {
+    return 1.0 / (2.0 * PI * one_minus_cos_max);
+}
+
+/// Veach's power heuristic (β = 2) weight for a sample drawn with pdf `f_pdf`
+/// when another strategy could have drawn it with pdf `g_pdf`.
+pub fn power_heuristic(f_pdf: f32, g_pdf: f32) -> f32 {
+    let f2 = f_pdf * f_pdf;
+    let g2 = g_pdf * g_pdf;
+
+    if f2 + g2 <= 0.0 {
+        return 0.0;
+    }
+
+    return f2 / (f2 + g2);
+}
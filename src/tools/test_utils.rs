@@ -0,0 +1,13 @@
+//! Helpers shared by the unit tests.
+
+use super::vector3::Vector3;
+
+/// Asserts that two vectors are equal up to floating point error.
+pub fn assert_near(a: &Vector3, b: &Vector3) {
+    assert!(
+        Vector3::magnitude(&Vector3::sub(a, b)) < 1e-5,
+        "{} != {}",
+        a,
+        b
+    );
+}
@@ -0,0 +1,271 @@
+use crate::objects::ray::Ray;
+
+use super::{aabb::Aabb, intersectable::Intersection, matrix4::Matrix4, vector3::Vector3};
+
+/// Affine placement of an object, mapping its local space to world space.
+/// The inverse is kept alongside so rays can be moved into object space.
+///
+/// The builder methods apply in call order:
+/// `Transform::identity().scale(..).rotate_euler(..).translate(..)` scales
+/// first and translates last.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub matrix: Matrix4,
+    pub inverse: Matrix4,
+}
+
+impl Transform {
+    pub fn identity() -> Transform {
+        return Transform {
+            matrix: Matrix4::identity(),
+            inverse: Matrix4::identity(),
+        };
+    }
+
+    /// `None` when `matrix` is not invertible.
+    pub fn from_matrix(matrix: Matrix4) -> Option<Transform> {
+        return Some(Transform {
+            matrix,
+            inverse: Matrix4::inverse(&matrix)?,
+        });
+    }
+
+    pub fn translation(offset: &Vector3) -> Transform {
+        return Transform::identity().translate(offset);
+    }
+
+    pub fn is_identity(&self) -> bool {
+        return self.matrix == Matrix4::identity();
+    }
+
+    /// This transform followed by `other`.
+    pub fn then(&self, other: &Transform) -> Transform {
+        return Transform {
+            matrix: Matrix4::multiply(&other.matrix, &self.matrix),
+            inverse: Matrix4::multiply(&self.inverse, &other.inverse),
+        };
+    }
+
+    pub fn get_inverse(&self) -> Transform {
+        return Transform {
+            matrix: self.inverse,
+            inverse: self.matrix,
+        };
+    }
+
+    pub fn translate(&self, offset: &Vector3) -> Transform {
+        return self.then(&Transform {
+            matrix: Matrix4::translation(offset),
+            inverse: Matrix4::translation(&-*offset),
+        });
+    }
+
+    /// Non-uniform scaling; zero factors leave the transform singular.
+    pub fn scale(&self, factors: &Vector3) -> Transform {
+        return self.then(&Transform {
+            matrix: Matrix4::scaling(factors),
+            inverse: Matrix4::scaling(&Vector3::new(
+                1.0 / factors.x,
+                1.0 / factors.y,
+                1.0 / factors.z,
+            )),
+        });
+    }
+
+    /// Rotation about x, then y, then z, in degrees.
+    pub fn rotate_euler(&self, degrees: &Vector3) -> Transform {
+        let rotation = Matrix4::rotation_euler(degrees);
+
+        return self.then(&Transform {
+            matrix: rotation,
+            inverse: Matrix4::transpose(&rotation),
+        });
+    }
+
+    pub fn rotate_axis_angle(&self, axis: &Vector3, degrees: f32) -> Transform {
+        let rotation = Matrix4::rotation_axis_angle(axis, degrees);
+
+        return self.then(&Transform {
+            matrix: rotation,
+            inverse: Matrix4::transpose(&rotation),
+        });
+    }
+
+    /// The scale factor when the transform scales all directions alike, as
+    /// rotations, translations and uniform scaling do; `None` otherwise.
+    pub fn uniform_scale(&self) -> Option<f32> {
+        let axes = [
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        ]
+        .map(|axis| self.vector_to_world(&axis));
+        let scale = Vector3::magnitude(&axes[0]);
+        let tolerance = 1e-4 * scale;
+
+        for i in 0..3 {
+            if (Vector3::magnitude(&axes[i]) - scale).abs() > tolerance
+                || Vector3::dot_product(&axes[i], &axes[(i + 1) % 3]).abs() > tolerance * scale
+            {
+                return None;
+            }
+        }
+
+        return Some(scale);
+    }
+
+    pub fn point_to_world(&self, point: &Vector3) -> Vector3 {
+        return Matrix4::transform_point(&self.matrix, point);
+    }
+
+    pub fn vector_to_world(&self, vector: &Vector3) -> Vector3 {
+        return Matrix4::transform_vector(&self.matrix, vector);
+    }
+
+    /// Normals transform by the inverse transpose to stay perpendicular to
+    /// non-uniformly scaled surfaces. The result is normalized.
+    pub fn normal_to_world(&self, normal: &Vector3) -> Vector3 {
+        let transformed = Matrix4::transform_vector(&Matrix4::transpose(&self.inverse), normal);
+
+        if Vector3::magnitude(&transformed) <= 0.0 {
+            return transformed;
+        }
+
+        return Vector3::normalize(&transformed);
+    }
+
+    /// World-space box enclosing the transformed corners of `bounds`.
+    pub fn bounds_to_world(&self, bounds: &Aabb) -> Aabb {
+        if bounds.is_empty() {
+            return *bounds;
+        }
+
+        let extremes = [bounds.min, bounds.max];
+        let corners: Vec<Vector3> = (0..8)
+            .map(|i| {
+                self.point_to_world(&Vector3::new(
+                    extremes[i & 1].x,
+                    extremes[(i >> 1) & 1].y,
+                    extremes[(i >> 2) & 1].z,
+                ))
+            })
+            .collect();
+
+        return Aabb::from_points(&corners);
+    }
+
+    /// Intersects a world-space `ray` with an object through `intersect`, which
+    /// works in the object's local space. The local ray is normalized, so its
+    /// distances, and the `t_min`..`t_max` range passed down with it, are
+    /// rescaled by the length of the transformed direction; the hit is then
+    /// moved back to world space.
+    pub fn intersect<'a>(
+        &self,
+        ray: &Ray,
+        t_min: f32,
+        t_max: f32,
+        intersect: impl FnOnce(&Ray, f32, f32) -> Option<Intersection<'a>>,
+    ) -> Option<Intersection<'a>> {
+        if self.is_identity() {
+            return intersect(ray, t_min, t_max);
+        }
+
+        let direction = Matrix4::transform_vector(&self.inverse, &ray.direction);
+        let length = Vector3::magnitude(&direction);
+
+        if length <= 0.0 {
+            return None;
+        }
+
+        let local_ray = Ray::new(
+            &Matrix4::transform_point(&self.inverse, &ray.origin),
+            &direction,
+        );
+        let local = intersect(&local_ray, t_min * length, t_max * length)?;
+
+        let outward = |normal: &Vector3| {
+            if local.front_face {
+                *normal
+            } else {
+                -*normal
+            }
+        };
+
+        let mut world = Intersection::new(
+            ray,
+            local.distance / length,
+            self.normal_to_world(&outward(&local.normal)),
+            local.uv,
+            local.object.clone(),
+        )
+        .with_shading_normal(self.normal_to_world(&outward(&local.shading_normal)));
+        world.face_index = local.face_index;
+
+        return Some(world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::{material::Material, sphere::Sphere};
+    use crate::tools::{color_tools::Color, intersectable::Intersectable};
+
+    #[test]
+    fn scaled_sphere_hit_at_world_distance() {
+        // Unit sphere grown to radius 2 and centered on (0, 0, 10).
+        let sphere = Sphere::new(Vector3::zero(), 1.0, Material::diffuse(Color::White))
+            .with_transform(
+                Transform::identity()
+                    .scale(&Vector3::new(2.0, 2.0, 2.0))
+                    .translate(&Vector3::new(0.0, 0.0, 10.0)),
+            );
+        let ray = Ray::new(&Vector3::zero(), &Vector3::new(0.0, 0.0, 1.0));
+
+        let hit = sphere.get_intersection(&ray, 0.0, f32::INFINITY).unwrap();
+        assert!((hit.distance - 8.0).abs() < 1e-4);
+        assert!(Vector3::magnitude(&Vector3::sub(&hit.point, &Vector3::new(0, 0, 8))) < 1e-4);
+        assert!(Vector3::magnitude(&Vector3::sub(&hit.normal, &Vector3::new(0, 0, -1))) < 1e-4);
+
+        // The range is in world units too.
+        assert!(sphere.get_intersection(&ray, 0.0, 7.9).is_none());
+        assert!(sphere.get_intersection(&ray, 0.0, 8.1).is_some());
+    }
+
+    #[test]
+    fn normals_stay_perpendicular_under_non_uniform_scale() {
+        let transform = Transform::identity()
+            .scale(&Vector3::new(4.0, 1.0, 0.5))
+            .rotate_euler(&Vector3::new(10.0, 20.0, 30.0));
+        let normal = Vector3::normalize(&Vector3::new(1.0, 1.0, 0.0));
+        // Two directions along the surface the normal belongs to.
+        let tangents = [Vector3::new(1.0, -1.0, 0.0), Vector3::new(0.0, 0.0, 1.0)];
+
+        let world_normal = transform.normal_to_world(&normal);
+        assert!((Vector3::magnitude(&world_normal) - 1.0).abs() < 1e-5);
+
+        for tangent in tangents {
+            let world_tangent = Vector3::normalize(&transform.vector_to_world(&tangent));
+            assert!(Vector3::dot_product(&world_normal, &world_tangent).abs() < 1e-5);
+        }
+
+        // Transforming the normal like a direction would tilt it.
+        let naive = Vector3::normalize(&transform.vector_to_world(&normal));
+        let world_tangent = Vector3::normalize(&transform.vector_to_world(&tangents[0]));
+        assert!(Vector3::dot_product(&naive, &world_tangent).abs() > 0.1);
+    }
+
+    #[test]
+    fn then_composes_inverse() {
+        let transform = Transform::identity()
+            .scale(&Vector3::new(2.0, 3.0, 4.0))
+            .rotate_axis_angle(&Vector3::new(0.0, 1.0, 1.0), 40.0)
+            .translate(&Vector3::new(5.0, 0.0, -1.0));
+        let point = Vector3::new(0.3, -2.0, 7.0);
+
+        let round_trip = transform
+            .get_inverse()
+            .point_to_world(&transform.point_to_world(&point));
+        assert!(Vector3::magnitude(&Vector3::sub(&round_trip, &point)) < 1e-4);
+    }
+}